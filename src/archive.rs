@@ -0,0 +1,296 @@
+use crate::error::{AppError, Result};
+use crate::parser::{Block, Conversation, Turn};
+use chrono::{SecondsFormat, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use std::path::{Path, PathBuf};
+
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS conversations (
+    id TEXT PRIMARY KEY,
+    title TEXT NOT NULL,
+    platform TEXT NOT NULL,
+    project TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    output_file TEXT,
+    archived_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS messages (
+    id INTEGER PRIMARY KEY,
+    conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+    branch INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    message_id TEXT,
+    role TEXT NOT NULL,
+    created_at TEXT,
+    model TEXT,
+    text TEXT NOT NULL,
+    thinking TEXT
+);
+
+CREATE TABLE IF NOT EXISTS attachments (
+    id INTEGER PRIMARY KEY,
+    message_id INTEGER NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    content TEXT,
+    size_bytes INTEGER
+);
+
+CREATE TABLE IF NOT EXISTS artifacts (
+    id INTEGER PRIMARY KEY,
+    message_id INTEGER NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    artifact_id TEXT,
+    title TEXT,
+    language TEXT,
+    content TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS messages_conversation ON messages(conversation_id);
+CREATE INDEX IF NOT EXISTS attachments_message ON attachments(message_id);
+CREATE INDEX IF NOT EXISTS artifacts_message ON artifacts(message_id);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(text, thinking);
+"#;
+
+/// Whether a conversation was new to the archive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Upsert {
+    Inserted,
+    Updated,
+}
+
+/// A message matching a search, with the file the conversation was written to.
+pub struct SearchHit {
+    pub conversation_id: String,
+    pub title: String,
+    pub platform: String,
+    pub role: String,
+    pub created_at: Option<String>,
+    pub snippet: String,
+    pub output_file: Option<String>,
+}
+
+/// A SQLite database of converted conversations with a full-text index
+/// over their messages.
+pub struct Archive {
+    connection: Connection,
+}
+
+/// Text and thinking of a message as they are indexed for search.
+fn message_text(turn: &Turn) -> (String, Option<String>) {
+    let mut text = Vec::new();
+    let mut thinking = Vec::new();
+
+    for block in &turn.blocks {
+        match block {
+            Block::Text(value) => text.push(value.as_str()),
+            Block::Execution { code, stdout, .. } => {
+                text.push(code);
+                text.extend(stdout.as_deref());
+            }
+            Block::Citation { text: quote, .. } => text.push(quote),
+            Block::Thinking { text: value, .. } => thinking.push(value.as_str()),
+            _ => {}
+        }
+    }
+
+    let thinking = (!thinking.is_empty()).then(|| thinking.join("\n\n"));
+    (text.join("\n\n"), thinking)
+}
+
+fn insert_turn(
+    transaction: &Transaction,
+    conversation_id: &str,
+    branch: usize,
+    position: usize,
+    turn: &Turn,
+) -> Result<()> {
+    let (text, thinking) = message_text(turn);
+    transaction.execute(
+        "INSERT INTO messages
+            (conversation_id, branch, position, message_id, role, created_at, model, text, thinking)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            conversation_id,
+            branch as i64,
+            position as i64,
+            turn.id,
+            turn.role.name(),
+            turn.created_at
+                .map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true)),
+            turn.model,
+            text,
+            thinking,
+        ],
+    )?;
+    let row_id = transaction.last_insert_rowid();
+
+    transaction.execute(
+        "INSERT INTO messages_fts (rowid, text, thinking) VALUES (?1, ?2, ?3)",
+        params![row_id, text, thinking],
+    )?;
+
+    for block in &turn.blocks {
+        match block {
+            Block::Attachment {
+                name,
+                content,
+                size_bytes,
+            } => {
+                transaction.execute(
+                    "INSERT INTO attachments (message_id, name, content, size_bytes)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![row_id, name, content, size_bytes.map(|size| size as i64)],
+                )?;
+            }
+            Block::Artifact {
+                id,
+                title,
+                language,
+                content,
+                ..
+            } => {
+                transaction.execute(
+                    "INSERT INTO artifacts (message_id, artifact_id, title, language, content)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![row_id, id, title, language, content],
+                )?;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+impl Archive {
+    /// Opens the archive, creating the database and its tables if needed.
+    pub fn open(path: &Path) -> Result<Self> {
+        let connection = Connection::open(path)?;
+        connection.pragma_update(None, "foreign_keys", true)?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self { connection })
+    }
+
+    /// Stores a conversation with all its branches, replacing any earlier
+    /// copy with the same id, so archiving the same export twice is harmless.
+    pub fn upsert(
+        &self,
+        conversation: &Conversation,
+        output_file: Option<&Path>,
+    ) -> Result<Upsert> {
+        let id = conversation.id.as_deref().ok_or_else(|| {
+            AppError::Export(format!(
+                "Conversation '{}' has no id and cannot be archived",
+                conversation.title
+            ))
+        })?;
+
+        // Search results link to the file from anywhere
+        let output_file = output_file.map(|path| {
+            path.canonicalize()
+                .unwrap_or_else(|_| PathBuf::from(path))
+                .display()
+                .to_string()
+        });
+
+        let transaction = self.connection.unchecked_transaction()?;
+
+        let existing = transaction
+            .query_row(
+                "SELECT 1 FROM conversations WHERE id = ?1",
+                params![id],
+                |_| Ok(()),
+            )
+            .optional()?;
+
+        // Messages are replaced as a whole, as they have no stable ids
+        // on every platform
+        transaction.execute(
+            "DELETE FROM messages_fts WHERE rowid IN
+                (SELECT id FROM messages WHERE conversation_id = ?1)",
+            params![id],
+        )?;
+        transaction.execute(
+            "DELETE FROM messages WHERE conversation_id = ?1",
+            params![id],
+        )?;
+
+        transaction.execute(
+            "INSERT INTO conversations
+                (id, title, platform, project, created_at, updated_at, output_file, archived_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT(id) DO UPDATE SET
+                title = excluded.title,
+                platform = excluded.platform,
+                project = excluded.project,
+                created_at = excluded.created_at,
+                updated_at = excluded.updated_at,
+                output_file = COALESCE(excluded.output_file, conversations.output_file),
+                archived_at = excluded.archived_at",
+            params![
+                id,
+                conversation.title,
+                conversation.source.platform_name(),
+                conversation.project_name,
+                conversation
+                    .created_at
+                    .to_rfc3339_opts(SecondsFormat::Secs, true),
+                conversation
+                    .updated_at
+                    .to_rfc3339_opts(SecondsFormat::Secs, true),
+                output_file,
+                Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            ],
+        )?;
+
+        // Branch 0 is the exported path, alternate branches follow
+        for (position, turn) in conversation.turns.iter().enumerate() {
+            insert_turn(&transaction, id, 0, position, turn)?;
+        }
+        for (branch, alternate) in conversation.branches.iter().enumerate() {
+            for (position, turn) in alternate.turns.iter().enumerate() {
+                insert_turn(&transaction, id, branch + 1, position, turn)?;
+            }
+        }
+
+        transaction.commit()?;
+
+        Ok(match existing {
+            Some(()) => Upsert::Updated,
+            None => Upsert::Inserted,
+        })
+    }
+
+    /// Full-text search over message text and thinking, best matches first.
+    /// The query uses SQLite FTS5 syntax, e.g. `rust AND "borrow checker"`.
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>> {
+        let mut statement = self.connection.prepare(
+            "SELECT c.id, c.title, c.platform, m.role, m.created_at,
+                    snippet(messages_fts, -1, '[', ']', '...', 16), c.output_file
+             FROM messages_fts
+             JOIN messages m ON m.id = messages_fts.rowid
+             JOIN conversations c ON c.id = m.conversation_id
+             WHERE messages_fts MATCH ?1
+             ORDER BY rank
+             LIMIT ?2",
+        )?;
+
+        let hits = statement
+            .query_map(params![query, limit as i64], |row| {
+                Ok(SearchHit {
+                    conversation_id: row.get(0)?,
+                    title: row.get(1)?,
+                    platform: row.get(2)?,
+                    role: row.get(3)?,
+                    created_at: row.get(4)?,
+                    snippet: row.get(5)?,
+                    output_file: row.get(6)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(hits)
+    }
+}
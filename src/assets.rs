@@ -0,0 +1,199 @@
+use crate::error::Result;
+use crate::parser::{Block, Conversation};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use once_cell::sync::{Lazy, OnceCell};
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+/// File id at the start of an asset's file name, e.g. `file-AbC123` in
+/// `file-AbC123-photo.png` or `file_00000000abcd` in `file_00000000abcd-….png`.
+static ASSET_NAME_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(file[-_][A-Za-z0-9]+)").unwrap());
+
+/// How many folders deep the directory of an input is searched, which
+/// covers the `dalle-generations` and `user-…` folders of an export.
+const MAX_ASSET_DEPTH: usize = 2;
+
+/// The image files of an account export, found by their file id: the
+/// files next to the input, or inside it for a zip. The export is only
+/// searched once an image is looked up.
+pub struct Assets {
+    input_file: PathBuf,
+    is_zip: bool,
+    /// File id to the file's path, or its entry name in a zip
+    index: OnceCell<HashMap<String, String>>,
+}
+
+fn asset_id(file_name: &str) -> Option<&str> {
+    ASSET_NAME_RE
+        .captures(file_name)
+        .and_then(|captures| captures.get(1))
+        .map(|id| id.as_str())
+}
+
+fn index_dir(dir: &Path, depth: usize, index: &mut HashMap<String, String>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if depth < MAX_ASSET_DEPTH {
+                index_dir(&path, depth + 1, index)?;
+            }
+            continue;
+        }
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        if let Some(id) = asset_id(&file_name) {
+            index.insert(id.to_string(), path.display().to_string());
+        }
+    }
+    Ok(())
+}
+
+fn index_zip(path: &Path) -> Result<HashMap<String, String>> {
+    let archive = ZipArchive::new(File::open(path)?)?;
+    let mut index = HashMap::new();
+    for name in archive.file_names() {
+        let file_name = name.rsplit('/').next().unwrap_or(name);
+        if let Some(id) = asset_id(file_name) {
+            index.insert(id.to_string(), name.to_string());
+        }
+    }
+    Ok(index)
+}
+
+impl Assets {
+    pub fn new(input_file: &Path) -> Self {
+        Self {
+            input_file: input_file.to_path_buf(),
+            is_zip: input_file
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("zip")),
+            index: OnceCell::new(),
+        }
+    }
+
+    /// An export that cannot be read has no assets, so its images are
+    /// shown as placeholders.
+    fn index(&self) -> &HashMap<String, String> {
+        self.index.get_or_init(|| {
+            let index = if self.is_zip {
+                index_zip(&self.input_file)
+            } else {
+                let dir = match self.input_file.parent() {
+                    Some(dir) if !dir.as_os_str().is_empty() => dir,
+                    _ => Path::new("."),
+                };
+                let mut index = HashMap::new();
+                index_dir(dir, 0, &mut index).map(|_| index)
+            };
+            index.unwrap_or_default()
+        })
+    }
+
+    /// Copies the asset with the given file id into `dir`, returning its
+    /// file name, or `None` if the export does not include it.
+    pub fn copy(&self, id: &str, dir: &Path) -> Result<Option<String>> {
+        let Some(source) = self.index().get(id) else {
+            return Ok(None);
+        };
+        let file_name = source.rsplit(['/', '\\']).next().unwrap_or(source);
+        let target = dir.join(file_name);
+
+        if !target.exists() {
+            fs::create_dir_all(dir)?;
+            if self.is_zip {
+                let mut archive = ZipArchive::new(File::open(&self.input_file)?)?;
+                let mut entry = archive.by_name(source)?;
+                io::copy(&mut entry, &mut File::create(&target)?)?;
+            } else {
+                fs::copy(source, &target)?;
+            }
+        }
+
+        Ok(Some(file_name.to_string()))
+    }
+
+    /// Reads the asset with the given file id as a `data:` URI, or returns
+    /// `None` if the export does not include it.
+    pub fn data_uri(&self, id: &str) -> Result<Option<String>> {
+        let Some(source) = self.index().get(id) else {
+            return Ok(None);
+        };
+
+        let mut bytes = Vec::new();
+        if self.is_zip {
+            let mut archive = ZipArchive::new(File::open(&self.input_file)?)?;
+            archive.by_name(source)?.read_to_end(&mut bytes)?;
+        } else {
+            bytes = fs::read(source)?;
+        }
+
+        Ok(Some(format!(
+            "data:{};base64,{}",
+            media_type(source),
+            STANDARD.encode(bytes)
+        )))
+    }
+}
+
+/// Media type of an image file, from its extension.
+fn media_type(file_name: &str) -> &'static str {
+    let extension = Path::new(file_name)
+        .extension()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_ascii_lowercase();
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Name of the directory next to an output file that its images are
+/// copied to, e.g. `chat_assets` for `chat.md`.
+pub fn asset_dir_name(output_file: &Path) -> String {
+    let stem = output_file
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+    format!("{}_assets", stem)
+}
+
+/// Copies the images of a conversation found in the export next to its
+/// output file, and points the image blocks at the copies. With `embed`
+/// the images are inlined as `data:` URIs instead, so the output stands
+/// on its own.
+pub fn copy_images(
+    conversation: &mut Conversation,
+    assets: &Assets,
+    output_file: &Path,
+    embed: bool,
+) -> Result<()> {
+    let dir_name = asset_dir_name(output_file);
+    let dir = output_file.with_file_name(&dir_name);
+
+    let turns = conversation.turns.iter_mut().chain(
+        conversation
+            .branches
+            .iter_mut()
+            .flat_map(|branch| branch.turns.iter_mut()),
+    );
+    for block in turns.flat_map(|turn| turn.blocks.iter_mut()) {
+        if let Block::Image { asset, path, .. } = block {
+            if embed {
+                *path = assets.data_uri(asset)?;
+            } else if let Some(file_name) = assets.copy(asset, &dir)? {
+                *path = Some(format!("{}/{}", dir_name, file_name));
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,8 +1,9 @@
-use super::cli::CliArgs;
+use super::cli::{CliArgs, Command};
 use super::constants::*;
 use super::error::{ConfigError, Result};
-use super::model::{AppConfig, MarkdownConfig};
-use super::paths::resolve_paths;
+use super::model::{AppConfig, RunConfig, SearchConfig, WatchConfig};
+use super::paths::{resolve_base_dir, resolve_paths};
+use super::types::ChatSource;
 use config::{Config, Environment, File};
 use dirs::home_dir;
 use dotenv::dotenv;
@@ -17,11 +18,24 @@ fn build_app_config(cli_args: &CliArgs) -> Result<AppConfig> {
     let mut builder = Config::builder();
 
     // Set default values
+    builder = builder.set_default(KEY_CHAT_SOURCE, DEFAULT_CHAT_SOURCE)?;
     builder = builder.set_default(KEY_USER_NAME, DEFAULT_USER_NAME)?;
     builder = builder.set_default(KEY_TIMEZONE, DEFAULT_TIMEZONE)?;
     builder = builder.set_default(KEY_SHOW_REASONING, DEFAULT_SHOW_REASONING)?;
+    builder = builder.set_default(KEY_CODE_EXECUTION, DEFAULT_CODE_EXECUTION)?;
+    builder = builder.set_default(KEY_STRIP_CITATIONS, DEFAULT_STRIP_CITATIONS)?;
+    builder = builder.set_default(KEY_COLLAPSE_BROWSING, DEFAULT_COLLAPSE_BROWSING)?;
+    builder = builder.set_default(KEY_SHOW_BRANCHES, DEFAULT_SHOW_BRANCHES)?;
+    builder = builder.set_default(KEY_FORMAT, DEFAULT_FORMAT)?;
+    builder = builder.set_default(KEY_SYSTEM_MESSAGES, DEFAULT_SYSTEM_MESSAGES)?;
+    builder = builder.set_default(KEY_ATTACHMENTS, DEFAULT_ATTACHMENTS)?;
+    builder = builder.set_default(KEY_UPDATE, DEFAULT_UPDATE)?;
+    builder = builder.set_default(KEY_ARTIFACT_FILES, DEFAULT_ARTIFACT_FILES)?;
+    builder = builder.set_default(KEY_MODEL_HEADERS, DEFAULT_MODEL_HEADERS)?;
+    builder = builder.set_default(KEY_HIDDEN_CONTEXT, DEFAULT_HIDDEN_CONTEXT)?;
     builder = builder.set_default(KEY_BASE_DIR, DEFAULT_BASE_DIR)?;
     builder = builder.set_default(KEY_INLINE_OUTPUT, DEFAULT_INLINE_OUTPUT)?;
+    builder = builder.set_default(KEY_ARCHIVE_FILE, DEFAULT_ARCHIVE_FILE)?;
 
     // Load .env file as environment variables.
     // Existing env vars are not overridden
@@ -95,10 +109,23 @@ fn build_app_config(cli_args: &CliArgs) -> Result<AppConfig> {
         (ENV_CHAT_SOURCE, KEY_CHAT_SOURCE),
         (ENV_CHAT_TIMEZONE, KEY_TIMEZONE),
         (ENV_CHAT_SHOW_REASONING, KEY_SHOW_REASONING),
+        (ENV_CHAT_CODE_EXECUTION, KEY_CODE_EXECUTION),
+        (ENV_CHAT_STRIP_CITATIONS, KEY_STRIP_CITATIONS),
+        (ENV_CHAT_COLLAPSE_BROWSING, KEY_COLLAPSE_BROWSING),
+        (ENV_CHAT_SHOW_BRANCHES, KEY_SHOW_BRANCHES),
+        (ENV_CHAT_LEAF, KEY_LEAF),
+        (ENV_CHAT_FORMAT, KEY_FORMAT),
+        (ENV_CHAT_SYSTEM_MESSAGES, KEY_SYSTEM_MESSAGES),
+        (ENV_CHAT_ATTACHMENTS, KEY_ATTACHMENTS),
+        (ENV_CHAT_UPDATE, KEY_UPDATE),
+        (ENV_CHAT_ARTIFACT_FILES, KEY_ARTIFACT_FILES),
+        (ENV_CHAT_MODEL_HEADERS, KEY_MODEL_HEADERS),
+        (ENV_CHAT_HIDDEN_CONTEXT, KEY_HIDDEN_CONTEXT),
         (ENV_CHAT_BASE_DIR, KEY_BASE_DIR),
         (ENV_CHAT_INLINE_OUTPUT, KEY_INLINE_OUTPUT),
         (ENV_CHAT_INPUT_FILE, KEY_INPUT_FILE),
         (ENV_CHAT_OUTPUT_FILE, KEY_OUTPUT_FILE),
+        (ENV_CHAT_ARCHIVE_FILE, KEY_ARCHIVE_FILE),
         (ENV_CHAT_USER_NAME, KEY_USER_NAME),
         (ENV_CHAT_AI_NAME, KEY_AI_NAME),
     ] {
@@ -125,15 +152,28 @@ fn build_app_config(cli_args: &CliArgs) -> Result<AppConfig> {
         title: config.get_string(KEY_TITLE).ok(),
         timezone: config.get_string(KEY_TIMEZONE)?,
         reasoning: config.get_bool(KEY_SHOW_REASONING)?,
+        code_execution: config.get_bool(KEY_CODE_EXECUTION)?,
+        strip_citations: config.get_bool(KEY_STRIP_CITATIONS)?,
+        collapse_browsing: config.get_bool(KEY_COLLAPSE_BROWSING)?,
+        branches: config.get_bool(KEY_SHOW_BRANCHES)?,
+        leaf: config.get_string(KEY_LEAF).ok(),
+        format: config.get_string(KEY_FORMAT)?.parse()?,
+        system_messages: config.get_bool(KEY_SYSTEM_MESSAGES)?,
+        attachments: config.get_bool(KEY_ATTACHMENTS)?,
+        update: config.get_bool(KEY_UPDATE)?,
+        artifact_files: config.get_bool(KEY_ARTIFACT_FILES)?,
+        model_headers: config.get_bool(KEY_MODEL_HEADERS)?,
+        hidden_context: config.get_bool(KEY_HIDDEN_CONTEXT)?,
         base_dir: config.get_string(KEY_BASE_DIR)?,
         inline_output: config.get_bool(KEY_INLINE_OUTPUT)?,
-        input_file: config.get_string(KEY_INPUT_FILE).ok(),
+        input_files: config.get_string(KEY_INPUT_FILE).into_iter().collect(),
         output_file: config.get_string(KEY_OUTPUT_FILE).ok(),
+        archive_file: config.get_string(KEY_ARCHIVE_FILE)?,
     };
 
     // Override existing config values with cli args if provided
     if let Some(chat_source) = &cli_args.chat_source {
-        app_config.chat_source = Some(chat_source.clone());
+        app_config.chat_source = Some(*chat_source);
     }
     if let Some(ai_name) = &cli_args.ai_name {
         app_config.ai_name = Some(ai_name.clone());
@@ -150,33 +190,62 @@ fn build_app_config(cli_args: &CliArgs) -> Result<AppConfig> {
     if let Some(reasoning) = &cli_args.reasoning {
         app_config.reasoning = *reasoning;
     }
+    if let Some(code_execution) = &cli_args.code_execution {
+        app_config.code_execution = *code_execution;
+    }
+    if let Some(strip_citations) = &cli_args.strip_citations {
+        app_config.strip_citations = *strip_citations;
+    }
+    if let Some(collapse_browsing) = &cli_args.collapse_browsing {
+        app_config.collapse_browsing = *collapse_browsing;
+    }
+    if let Some(branches) = &cli_args.branches {
+        app_config.branches = *branches;
+    }
+    if let Some(leaf) = &cli_args.leaf {
+        app_config.leaf = Some(leaf.clone());
+    }
+    if let Some(format) = &cli_args.format {
+        app_config.format = *format;
+    }
+    if let Some(system_messages) = &cli_args.system_messages {
+        app_config.system_messages = *system_messages;
+    }
+    if let Some(attachments) = &cli_args.attachments {
+        app_config.attachments = *attachments;
+    }
+    if let Some(update) = &cli_args.update {
+        app_config.update = *update;
+    }
+    if let Some(artifact_files) = &cli_args.artifact_files {
+        app_config.artifact_files = *artifact_files;
+    }
+    if let Some(model_headers) = &cli_args.model_headers {
+        app_config.model_headers = *model_headers;
+    }
+    if let Some(hidden_context) = &cli_args.hidden_context {
+        app_config.hidden_context = *hidden_context;
+    }
     if let Some(base_dir) = &cli_args.base_dir {
         app_config.base_dir = base_dir.clone();
     }
     if let Some(inline_output) = &cli_args.inline_output {
         app_config.inline_output = *inline_output;
     }
-    if let Some(input_file) = &cli_args.input_file {
-        app_config.input_file = Some(input_file.clone());
+    if !cli_args.input_file.is_empty() {
+        app_config.input_files = cli_args.input_file.clone();
     }
     if let Some(output_file) = &cli_args.output_file {
         app_config.output_file = Some(output_file.clone());
     }
-
-    // Throw an error if the chat source is not provided
-    // in any of the configuration options
-    if app_config.chat_source.is_none() {
-        return Err(ConfigError::MissingChatSource(
-            "Chat source is required but was not provided".to_string(),
-        ));
+    if let Some(archive_file) = &cli_args.archive_file {
+        app_config.archive_file = archive_file.clone();
     }
 
-    // Throw an error if the input file is not provided
-    // in any of the configuration options
-    if app_config.input_file.is_none() {
-        return Err(ConfigError::MissingInputFile(
-            "Input file is required but was not provided".to_string(),
-        ));
+    // Watch mode picks up chats from any platform, so it always detects
+    // the source of each, whatever source is configured
+    if cli_args.watch {
+        app_config.chat_source = Some(ChatSource::Auto);
     }
 
     // Update the ai name if it was not explicitly set and the existing
@@ -188,32 +257,112 @@ fn build_app_config(cli_args: &CliArgs) -> Result<AppConfig> {
         let name_from_cli = cli_args.ai_name.is_some();
         let name_from_env = std::env::var(ENV_CHAT_AI_NAME).is_ok();
 
-        // Override if source was set at higher level than name
+        // Override if source was set at higher level than name.
+        // An auto-detected source has no name until the input is
+        // parsed, so the platform default is applied when rendering.
         if (source_from_cli && !name_from_cli)
             || (source_from_env && !name_from_env && !name_from_cli)
         {
-            app_config.ai_name = Some(chat_source.default_ai_name().to_string());
+            app_config.ai_name = match chat_source {
+                ChatSource::Auto => None,
+                _ => Some(chat_source.default_ai_name().to_string()),
+            };
         }
     }
 
     Ok(app_config)
 }
 
-pub fn build_config(cli_args: &CliArgs) -> Result<MarkdownConfig> {
+pub fn build_config(cli_args: &CliArgs) -> Result<RunConfig> {
     // First build the basic app config
     let app_config = build_app_config(cli_args)?;
 
-    // Get the chat source
-    let chat_source = app_config.chat_source.clone().unwrap();
-    let ai_name = app_config.ai_name.clone().unwrap();
+    // Watch mode finds its inputs in the base directory as they are saved
+    if cli_args.watch {
+        return build_watch_config(cli_args, app_config);
+    }
+
+    // Throw an error if the input file is not provided
+    // in any of the configuration options
+    if app_config.input_files.is_empty() {
+        return Err(ConfigError::MissingInputFile(
+            "Input file is required but was not provided".to_string(),
+        ));
+    }
+
+    // Get the chat source, which is detected from the input if unset
+    let chat_source = app_config.chat_source.unwrap_or(ChatSource::Auto);
 
     // Then resolve paths and convert to markdown config
-    let (_, input_path, output_path) = resolve_paths(
+    // Datasets combine every input into one output file
+    let (base_dir, jobs) = resolve_paths(
         &app_config.base_dir,
         app_config.inline_output,
-        app_config.input_file.as_deref().unwrap(),
+        &app_config.input_files,
         app_config.output_file.as_deref(),
+        app_config.format.extension(),
+        app_config.format.is_dataset(),
     )?;
 
-    Ok(app_config.into_markdown_config(chat_source, ai_name, input_path, output_path))
+    // The archive lives in the base directory unless given as an absolute path
+    let archive_path = match cli_args.command {
+        Some(Command::Archive) => Some(base_dir.join(&app_config.archive_file)),
+        _ => None,
+    };
+
+    // Archiving needs every conversation parsed, even if its output is current
+    let force = cli_args.force || archive_path.is_some();
+
+    Ok(app_config.into_run_config(chat_source, base_dir, jobs, force, None, archive_path))
+}
+
+fn build_watch_config(cli_args: &CliArgs, app_config: AppConfig) -> Result<RunConfig> {
+    if cli_args.output_file.is_some() {
+        return Err(ConfigError::Watch(
+            "An output file cannot be given, each chat is written next to its input".to_string(),
+        ));
+    }
+    if app_config.format.is_dataset() {
+        return Err(ConfigError::Watch(
+            "Datasets combine every input into one file and cannot be watched".to_string(),
+        ));
+    }
+
+    let chat_source = ChatSource::Auto;
+    let base_dir = resolve_base_dir(&app_config.base_dir)?;
+    let watch = WatchConfig {
+        base_dir: base_dir.clone(),
+        inline_output: app_config.inline_output,
+    };
+
+    let archive_path = match cli_args.command {
+        Some(Command::Archive) => Some(base_dir.join(&app_config.archive_file)),
+        _ => None,
+    };
+
+    let force = cli_args.force || archive_path.is_some();
+
+    Ok(app_config.into_run_config(
+        chat_source,
+        base_dir,
+        Vec::new(),
+        force,
+        Some(watch),
+        archive_path,
+    ))
+}
+
+pub fn build_search_config(cli_args: &CliArgs) -> Result<Option<SearchConfig>> {
+    let Some(Command::Search { query, limit }) = &cli_args.command else {
+        return Ok(None);
+    };
+
+    let app_config = build_app_config(cli_args)?;
+    let base_dir = resolve_base_dir(&app_config.base_dir)?;
+
+    Ok(Some(SearchConfig {
+        archive_file: base_dir.join(&app_config.archive_file),
+        query: query.clone(),
+        limit: *limit,
+    }))
 }
@@ -1,6 +1,6 @@
 use super::constants::*;
-use super::types::ChatSource;
-use clap::Parser;
+use super::types::{ChatSource, OutputFormat};
+use clap::{Parser, Subcommand};
 
 #[derive(Parser)]
 #[command(author, version, about)]
@@ -12,28 +12,32 @@ use clap::Parser;
     ),
 )]
 pub struct CliArgs {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Chat source (-s)
-    #[arg(short = 's', long, env = ENV_CHAT_SOURCE, ignore_case = true)]
+    #[arg(global = true, short = 's', long, env = ENV_CHAT_SOURCE, ignore_case = true)]
     pub chat_source: Option<ChatSource>,
 
     /// Name for the AI assistant (-a)
-    #[arg(short = 'a', long, env = ENV_CHAT_AI_NAME)]
+    #[arg(global = true, short = 'a', long, env = ENV_CHAT_AI_NAME)]
     pub ai_name: Option<String>,
 
     /// Name for the user (-u)
-    #[arg(short = 'u', long, env = ENV_CHAT_USER_NAME)]
+    #[arg(global = true, short = 'u', long, env = ENV_CHAT_USER_NAME)]
     pub user_name: Option<String>,
 
     /// Title for the chat(-t)
-    #[arg(short = 't', long, env = ENV_CHAT_TITLE)]
+    #[arg(global = true, short = 't', long, env = ENV_CHAT_TITLE)]
     pub title: Option<String>,
 
     /// Timezone for the chat (-z)
-    #[arg(short = 'z', long, env = ENV_CHAT_TIMEZONE)]
+    #[arg(global = true, short = 'z', long, env = ENV_CHAT_TIMEZONE)]
     pub timezone: Option<String>,
 
     /// Show reasoning (-r)
     #[arg(
+        global = true,
         short = 'r',
         long,
         env = ENV_CHAT_SHOW_REASONING,
@@ -43,12 +47,132 @@ pub struct CliArgs {
     )]
     pub reasoning: Option<bool>,
 
+    /// Show code interpreter runs and their output
+    #[arg(
+        global = true,
+        long,
+        env = ENV_CHAT_CODE_EXECUTION,
+        value_parser = clap::builder::BoolishValueParser::new(),
+        default_missing_value = "true",
+        num_args = 0..=1
+    )]
+    pub code_execution: Option<bool>,
+
+    /// Remove ChatGPT citation markers instead of turning them into footnotes
+    #[arg(
+        global = true,
+        long,
+        env = ENV_CHAT_STRIP_CITATIONS,
+        value_parser = clap::builder::BoolishValueParser::new(),
+        default_missing_value = "true",
+        num_args = 0..=1
+    )]
+    pub strip_citations: Option<bool>,
+
+    /// Collapse the pages ChatGPT browsed or searched into a foldable list
+    #[arg(
+        global = true,
+        long,
+        env = ENV_CHAT_COLLAPSE_BROWSING,
+        value_parser = clap::builder::BoolishValueParser::new(),
+        default_missing_value = "true",
+        num_args = 0..=1
+    )]
+    pub collapse_browsing: Option<bool>,
+
+    /// Show alternate branches, e.g. regenerated answers and edited prompts (-b)
+    #[arg(
+        global = true,
+        short = 'b',
+        long,
+        env = ENV_CHAT_SHOW_BRANCHES,
+        value_parser = clap::builder::BoolishValueParser::new(),
+        default_missing_value = "true",
+        num_args = 0..=1
+    )]
+    pub branches: Option<bool>,
+
+    /// Message id of the leaf whose path is exported (-l)
+    #[arg(global = true, short = 'l', long, env = ENV_CHAT_LEAF)]
+    pub leaf: Option<String>,
+
+    /// Output format (-f)
+    #[arg(global = true, short = 'f', long, env = ENV_CHAT_FORMAT, ignore_case = true)]
+    pub format: Option<OutputFormat>,
+
+    /// Include system and tool messages in datasets
+    #[arg(
+        global = true,
+        long,
+        env = ENV_CHAT_SYSTEM_MESSAGES,
+        value_parser = clap::builder::BoolishValueParser::new(),
+        default_missing_value = "true",
+        num_args = 0..=1
+    )]
+    pub system_messages: Option<bool>,
+
+    /// Include attachment contents
+    #[arg(
+        global = true,
+        long,
+        env = ENV_CHAT_ATTACHMENTS,
+        value_parser = clap::builder::BoolishValueParser::new(),
+        default_missing_value = "true",
+        num_args = 0..=1
+    )]
+    pub attachments: Option<bool>,
+
+    /// Append new messages to an earlier export instead of replacing it
+    #[arg(
+        global = true,
+        long,
+        env = ENV_CHAT_UPDATE,
+        value_parser = clap::builder::BoolishValueParser::new(),
+        default_missing_value = "true",
+        num_args = 0..=1
+    )]
+    pub update: Option<bool>,
+
+    /// Write Claude artifacts and ChatGPT canvas documents to files next to the output and link them
+    #[arg(
+        global = true,
+        long,
+        env = ENV_CHAT_ARTIFACT_FILES,
+        value_parser = clap::builder::BoolishValueParser::new(),
+        default_missing_value = "true",
+        num_args = 0..=1
+    )]
+    pub artifact_files: Option<bool>,
+
+    /// Show the models behind answers in the header, next to each answer and where they switch
+    #[arg(
+        global = true,
+        long,
+        env = ENV_CHAT_MODEL_HEADERS,
+        value_parser = clap::builder::BoolishValueParser::new(),
+        default_missing_value = "true",
+        num_args = 0..=1
+    )]
+    pub model_headers: Option<bool>,
+
+    /// Include messages ChatGPT hides, such as custom instructions, in a Hidden Context section
+    #[arg(
+        global = true,
+        long,
+        env = ENV_CHAT_HIDDEN_CONTEXT,
+        value_parser = clap::builder::BoolishValueParser::new(),
+        default_missing_value = "true",
+        num_args = 0..=1
+    )]
+    pub hidden_context: Option<bool>,
+
     /// Base directory for input/output files (-d)
-    #[arg(short = 'd', long, env = ENV_CHAT_BASE_DIR)]
+    #[arg(global = true, short = 'd', long, env = ENV_CHAT_BASE_DIR)]
     pub base_dir: Option<String>,
 
     /// Force saving of output to same directory as input (-p)
     #[arg(
+        global = true,
         short = 'p',
         long,
         env = ENV_CHAT_INLINE_OUTPUT,
@@ -58,15 +182,42 @@ pub struct CliArgs {
     )]
     pub inline_output: Option<bool>,
 
-    /// Input chat file (-i)
-    #[arg(short = 'i', long, env = ENV_CHAT_INPUT_FILE)]
-    pub input_file: Option<String>,
+    /// Input chat file(s); several can be combined into one dataset (-i)
+    #[arg(global = true, short = 'i', long, env = ENV_CHAT_INPUT_FILE, num_args = 1..)]
+    pub input_file: Vec<String>,
+
+    /// Watch the base directory and convert chats as they are saved (-w)
+    #[arg(global = true, short = 'w', long)]
+    pub watch: bool,
+
+    /// Rebuild outputs even if the manifest shows them up to date
+    #[arg(global = true, long)]
+    pub force: bool,
 
-    /// Output markdown file (-o)
-    #[arg(short = 'o', long, env = ENV_CHAT_OUTPUT_FILE)]
+    /// Output file (-o)
+    #[arg(global = true, short = 'o', long, env = ENV_CHAT_OUTPUT_FILE)]
     pub output_file: Option<String>,
 
+    /// SQLite archive, relative to the base directory
+    #[arg(global = true, long, env = ENV_CHAT_ARCHIVE_FILE)]
+    pub archive_file: Option<String>,
+
     /// Path to the config file (-c)
-    #[arg(short = 'c', long, env = ENV_APP_CONFIG_PATH)]
+    #[arg(global = true, short = 'c', long, env = ENV_APP_CONFIG_PATH)]
     pub config: Option<String>,
 }
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Convert the input files and load them into the SQLite archive
+    Archive,
+    /// Search the messages in the SQLite archive
+    Search {
+        /// Full-text query, e.g. `rust AND "borrow checker"`
+        query: String,
+
+        /// Maximum number of results (-n)
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+}
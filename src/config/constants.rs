@@ -12,10 +12,23 @@ pub const ENV_CHAT_USER_NAME: &str = "CHAT_USER_NAME";
 pub const ENV_CHAT_TITLE: &str = "CHAT_TITLE";
 pub const ENV_CHAT_TIMEZONE: &str = "CHAT_TIMEZONE";
 pub const ENV_CHAT_SHOW_REASONING: &str = "CHAT_SHOW_REASONING";
+pub const ENV_CHAT_CODE_EXECUTION: &str = "CHAT_CODE_EXECUTION";
+pub const ENV_CHAT_STRIP_CITATIONS: &str = "CHAT_STRIP_CITATIONS";
+pub const ENV_CHAT_COLLAPSE_BROWSING: &str = "CHAT_COLLAPSE_BROWSING";
+pub const ENV_CHAT_SHOW_BRANCHES: &str = "CHAT_SHOW_BRANCHES";
+pub const ENV_CHAT_LEAF: &str = "CHAT_LEAF";
+pub const ENV_CHAT_FORMAT: &str = "CHAT_FORMAT";
+pub const ENV_CHAT_SYSTEM_MESSAGES: &str = "CHAT_SYSTEM_MESSAGES";
+pub const ENV_CHAT_ATTACHMENTS: &str = "CHAT_ATTACHMENTS";
+pub const ENV_CHAT_UPDATE: &str = "CHAT_UPDATE";
+pub const ENV_CHAT_ARTIFACT_FILES: &str = "CHAT_ARTIFACT_FILES";
+pub const ENV_CHAT_MODEL_HEADERS: &str = "CHAT_MODEL_HEADERS";
+pub const ENV_CHAT_HIDDEN_CONTEXT: &str = "CHAT_HIDDEN_CONTEXT";
 pub const ENV_CHAT_BASE_DIR: &str = "CHAT_BASE_DIR";
 pub const ENV_CHAT_INLINE_OUTPUT: &str = "CHAT_INLINE_OUTPUT";
 pub const ENV_CHAT_INPUT_FILE: &str = "CHAT_INPUT_FILE";
 pub const ENV_CHAT_OUTPUT_FILE: &str = "CHAT_OUTPUT_FILE";
+pub const ENV_CHAT_ARCHIVE_FILE: &str = "CHAT_ARCHIVE_FILE";
 
 // Config builder keys
 pub const KEY_CHAT_SOURCE: &str = "chat_source";
@@ -24,15 +37,41 @@ pub const KEY_USER_NAME: &str = "user_name";
 pub const KEY_TITLE: &str = "title";
 pub const KEY_TIMEZONE: &str = "timezone";
 pub const KEY_SHOW_REASONING: &str = "reasoning";
+pub const KEY_CODE_EXECUTION: &str = "code_execution";
+pub const KEY_STRIP_CITATIONS: &str = "strip_citations";
+pub const KEY_COLLAPSE_BROWSING: &str = "collapse_browsing";
+pub const KEY_SHOW_BRANCHES: &str = "branches";
+pub const KEY_LEAF: &str = "leaf";
+pub const KEY_FORMAT: &str = "format";
+pub const KEY_SYSTEM_MESSAGES: &str = "system_messages";
+pub const KEY_ATTACHMENTS: &str = "attachments";
+pub const KEY_UPDATE: &str = "update";
+pub const KEY_ARTIFACT_FILES: &str = "artifact_files";
+pub const KEY_MODEL_HEADERS: &str = "model_headers";
+pub const KEY_HIDDEN_CONTEXT: &str = "hidden_context";
 pub const KEY_BASE_DIR: &str = "base_dir";
 pub const KEY_INLINE_OUTPUT: &str = "inline_output";
 pub const KEY_INPUT_FILE: &str = "input_file";
 pub const KEY_OUTPUT_FILE: &str = "output_file";
+pub const KEY_ARCHIVE_FILE: &str = "archive_file";
 
 // Default config values
+pub const DEFAULT_CHAT_SOURCE: &str = "auto";
 pub const DEFAULT_USER_NAME: &str = "User";
 pub const DEFAULT_TIMEZONE: &str = "UTC";
 pub const DEFAULT_SHOW_REASONING: bool = false;
+pub const DEFAULT_CODE_EXECUTION: bool = false;
+pub const DEFAULT_STRIP_CITATIONS: bool = false;
+pub const DEFAULT_COLLAPSE_BROWSING: bool = false;
+pub const DEFAULT_SHOW_BRANCHES: bool = false;
+pub const DEFAULT_FORMAT: &str = "markdown";
+pub const DEFAULT_SYSTEM_MESSAGES: bool = false;
+pub const DEFAULT_ATTACHMENTS: bool = true;
+pub const DEFAULT_UPDATE: bool = false;
+pub const DEFAULT_ARTIFACT_FILES: bool = false;
+pub const DEFAULT_MODEL_HEADERS: bool = false;
+pub const DEFAULT_HIDDEN_CONTEXT: bool = false;
 pub const DEFAULT_BASE_DIR: &str = ".";
 pub const DEFAULT_INLINE_OUTPUT: bool = true;
+pub const DEFAULT_ARCHIVE_FILE: &str = "aichatmd.sqlite";
 pub const DEFAULT_CONFIG_FILENAME: &str = ".aichatmd.json";
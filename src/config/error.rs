@@ -6,12 +6,16 @@ pub enum ConfigError {
     NoHomeDir,
     #[error("Configuration error: {0}")]
     Config(#[from] config::ConfigError),
-    #[error("Missing chat source: {0}")]
-    MissingChatSource(String),
     #[error("Invalid chat source: {0}")]
     ChatSource(String),
+    #[error("Invalid output format: {0}")]
+    OutputFormat(String),
     #[error("Missing input file: {0}")]
     MissingInputFile(String),
+    #[error("Multiple input files: {0}")]
+    MultipleInputs(String),
+    #[error("Invalid watch mode: {0}")]
+    Watch(String),
     #[error("Invalid path: {0}")]
     InvalidPath(String),
     #[error("File not found: {0}")]
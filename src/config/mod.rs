@@ -6,8 +6,9 @@ mod model;
 mod paths;
 mod types;
 
-pub use builder::build_config;
-pub use cli::CliArgs;
+pub use builder::{build_config, build_search_config};
+pub use cli::{CliArgs, Command};
 pub use error::ConfigError;
-pub use model::MarkdownConfig;
-pub use types::ChatSource;
+pub use model::{ConversionJob, MarkdownConfig, RunConfig, SearchConfig, WatchConfig};
+pub use paths::{is_chat_file, is_watched_file, resolve_job};
+pub use types::{ChatSource, OutputFormat};
@@ -1,4 +1,10 @@
-use super::types::ChatSource;
+use super::constants::{
+    DEFAULT_ARTIFACT_FILES, DEFAULT_ATTACHMENTS, DEFAULT_CODE_EXECUTION, DEFAULT_COLLAPSE_BROWSING,
+    DEFAULT_HIDDEN_CONTEXT, DEFAULT_MODEL_HEADERS, DEFAULT_SHOW_BRANCHES, DEFAULT_SHOW_REASONING,
+    DEFAULT_STRIP_CITATIONS, DEFAULT_SYSTEM_MESSAGES, DEFAULT_TIMEZONE, DEFAULT_UPDATE,
+    DEFAULT_USER_NAME,
+};
+use super::types::{ChatSource, OutputFormat};
 use serde::Deserialize;
 use std::path::PathBuf;
 
@@ -10,40 +16,159 @@ pub struct AppConfig {
     pub title: Option<String>,
     pub timezone: String,
     pub reasoning: bool,
+    pub code_execution: bool,
+    pub strip_citations: bool,
+    pub collapse_browsing: bool,
+    pub branches: bool,
+    pub leaf: Option<String>,
+    pub format: OutputFormat,
+    pub system_messages: bool,
+    pub attachments: bool,
+    pub update: bool,
+    pub artifact_files: bool,
+    pub model_headers: bool,
+    pub hidden_context: bool,
     pub base_dir: String,
     pub inline_output: bool,
-    pub input_file: Option<String>,
+    pub input_files: Vec<String>,
     pub output_file: Option<String>,
+    pub archive_file: String,
 }
 
+/// Settings that control how a conversation is rendered.
+/// When `ai_name` is not set, the platform's default name is used.
+/// When `leaf` is not set, the path the platform marks as current is used.
+/// `system_messages` only applies to dataset formats.
+/// `source_file` is the input file name, recorded in Obsidian frontmatter.
+/// `update` only applies to the Markdown formats, which then carry hidden
+/// anchors so a later export can be appended to them.
+/// `artifact_dir` is where artifact files are linked from, relative to the
+/// output. It is set for each output when `artifact_files` is on.
+#[derive(Clone, Debug)]
 pub struct MarkdownConfig {
-    pub chat_source: ChatSource,
-    pub ai_name: String,
+    pub ai_name: Option<String>,
     pub user_name: String,
     pub title: Option<String>,
     pub timezone: String,
     pub reasoning: bool,
+    pub code_execution: bool,
+    pub strip_citations: bool,
+    pub collapse_browsing: bool,
+    pub branches: bool,
+    pub leaf: Option<String>,
+    pub format: OutputFormat,
+    pub system_messages: bool,
+    pub attachments: bool,
+    pub update: bool,
+    pub artifact_files: bool,
+    pub model_headers: bool,
+    pub hidden_context: bool,
+    pub artifact_dir: Option<String>,
+    pub source_file: Option<String>,
+}
+
+impl Default for MarkdownConfig {
+    fn default() -> Self {
+        Self {
+            ai_name: None,
+            user_name: DEFAULT_USER_NAME.to_string(),
+            title: None,
+            timezone: DEFAULT_TIMEZONE.to_string(),
+            reasoning: DEFAULT_SHOW_REASONING,
+            code_execution: DEFAULT_CODE_EXECUTION,
+            strip_citations: DEFAULT_STRIP_CITATIONS,
+            collapse_browsing: DEFAULT_COLLAPSE_BROWSING,
+            branches: DEFAULT_SHOW_BRANCHES,
+            leaf: None,
+            format: OutputFormat::Markdown,
+            system_messages: DEFAULT_SYSTEM_MESSAGES,
+            attachments: DEFAULT_ATTACHMENTS,
+            update: DEFAULT_UPDATE,
+            artifact_files: DEFAULT_ARTIFACT_FILES,
+            model_headers: DEFAULT_MODEL_HEADERS,
+            hidden_context: DEFAULT_HIDDEN_CONTEXT,
+            artifact_dir: None,
+            source_file: None,
+        }
+    }
+}
+
+/// One input file and where its output goes. Every input of a dataset
+/// shares the same output file.
+#[derive(Clone, Debug)]
+pub struct ConversionJob {
     pub input_file: PathBuf,
     pub output_file: PathBuf,
 }
 
+/// Where watch mode looks for new chats, and whether their output is
+/// forced next to them.
+#[derive(Clone, Debug)]
+pub struct WatchConfig {
+    pub base_dir: PathBuf,
+    pub inline_output: bool,
+}
+
+/// A fully resolved conversion, as requested on the command line.
+/// `archive_file` is set when the conversations are also loaded into
+/// the archive. In watch mode there are no jobs up front; they are
+/// resolved as chats are saved. `force` rebuilds outputs the manifest
+/// in `base_dir` records as up to date.
+pub struct RunConfig {
+    pub chat_source: ChatSource,
+    pub base_dir: PathBuf,
+    pub jobs: Vec<ConversionJob>,
+    pub force: bool,
+    pub watch: Option<WatchConfig>,
+    pub archive_file: Option<PathBuf>,
+    pub markdown: MarkdownConfig,
+}
+
 impl AppConfig {
-    pub fn into_markdown_config(
+    pub fn into_run_config(
         self,
         chat_source: ChatSource,
-        ai_name: String,
-        input_path: PathBuf,
-        output_path: PathBuf,
-    ) -> MarkdownConfig {
-        MarkdownConfig {
+        base_dir: PathBuf,
+        jobs: Vec<ConversionJob>,
+        force: bool,
+        watch: Option<WatchConfig>,
+        archive_path: Option<PathBuf>,
+    ) -> RunConfig {
+        RunConfig {
             chat_source,
-            ai_name,
-            user_name: self.user_name,
-            title: self.title,
-            timezone: self.timezone,
-            reasoning: self.reasoning,
-            input_file: input_path,
-            output_file: output_path,
+            base_dir,
+            jobs,
+            force,
+            watch,
+            archive_file: archive_path,
+            markdown: MarkdownConfig {
+                ai_name: self.ai_name,
+                user_name: self.user_name,
+                title: self.title,
+                timezone: self.timezone,
+                reasoning: self.reasoning,
+                code_execution: self.code_execution,
+                strip_citations: self.strip_citations,
+                collapse_browsing: self.collapse_browsing,
+                branches: self.branches,
+                leaf: self.leaf,
+                format: self.format,
+                system_messages: self.system_messages,
+                attachments: self.attachments,
+                update: self.update,
+                artifact_files: self.artifact_files,
+                model_headers: self.model_headers,
+                hidden_context: self.hidden_context,
+                artifact_dir: None,
+                source_file: None,
+            },
         }
     }
 }
+
+/// A search of the archive, as requested on the command line.
+pub struct SearchConfig {
+    pub archive_file: PathBuf,
+    pub query: String,
+    pub limit: usize,
+}
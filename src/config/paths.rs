@@ -1,7 +1,46 @@
 use super::error::{ConfigError, Result};
+use super::model::ConversionJob;
+use super::types::OutputFormat;
+use clap::ValueEnum;
 use dirs::home_dir;
+use std::collections::HashMap;
+use std::fs;
 use std::path::{Path, PathBuf};
 
+const INPUT_EXTENSIONS: [&str; 3] = ["json", "txt", "zip"];
+
+/// Returns true for files a directory of downloads may hold chats in,
+/// leaving out hidden files and output this tool wrote itself.
+pub fn is_chat_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    let has_input_extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            INPUT_EXTENSIONS
+                .iter()
+                .any(|input| ext.eq_ignore_ascii_case(input))
+        });
+    let is_output = OutputFormat::value_variants().iter().any(|format| {
+        let extension = format.extension();
+        extension.contains('.') && name.ends_with(&format!(".{}", extension))
+    });
+
+    has_input_extension && !is_output && !name.starts_with('.')
+}
+
+/// Returns true for the chat files watch mode converts: the extensions
+/// `resolve_input` probes, as account export zips are not saved from a
+/// browser session.
+pub fn is_watched_file(path: &Path) -> bool {
+    is_chat_file(path)
+        && !path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+}
+
 struct PathResolver {
     base_dir: PathBuf,
 }
@@ -61,11 +100,54 @@ impl PathResolver {
         )))
     }
 
+    /// Expands an input argument into files. A glob pattern yields every
+    /// chat file it matches under the base directory, a directory every
+    /// chat file directly inside it, and anything else a single file.
+    fn resolve_inputs(&self, input: &str) -> Result<Vec<PathBuf>> {
+        if input.contains(['*', '?', '[']) {
+            let pattern = self.base_dir.join(input);
+            let pattern = pattern.to_str().ok_or_else(|| {
+                ConfigError::InvalidPath(format!("Pattern is not valid UTF-8: {}", input))
+            })?;
+            let paths = glob::glob(pattern).map_err(|err| {
+                ConfigError::InvalidPath(format!("Invalid pattern {}: {}", input, err))
+            })?;
+
+            let files: Vec<PathBuf> = paths
+                .filter_map(|path| path.ok())
+                .filter(|path| path.is_file() && is_chat_file(path))
+                .collect();
+            if files.is_empty() {
+                return Err(ConfigError::FileNotFound(format!(
+                    "No files match {}",
+                    input
+                )));
+            }
+            return Ok(files);
+        }
+
+        let dir = self.base_dir.join(input);
+        if dir.is_dir() {
+            let mut files = Vec::new();
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if path.is_file() && is_chat_file(&path) {
+                    files.push(path);
+                }
+            }
+            files.sort();
+            return Ok(files);
+        }
+
+        Ok(vec![self.resolve_input(input)?])
+    }
+
     fn resolve_output(
         &self,
         output_path: Option<&str>,
         input_path: &Path,
         inline_output: bool,
+        extension: &str,
     ) -> Result<PathBuf> {
         match output_path {
             Some(path) => {
@@ -79,8 +161,8 @@ impl PathResolver {
                         // Use provided extension
                         Ok(input_dir.join(output_file_name))
                     } else {
-                        // Use .md extension
-                        Ok(input_dir.join(output_file_name).with_extension("md"))
+                        // Use the output format's extension
+                        Ok(input_dir.join(output_file_name).with_extension(extension))
                     }
                 } else {
                     let path = Path::new(path);
@@ -91,10 +173,10 @@ impl PathResolver {
                         // Use provided extension
                         Ok(full_parent.join(path.file_name().unwrap()))
                     } else {
-                        // Use .md extension
+                        // Use the output format's extension
                         Ok(full_parent
                             .join(path.file_stem().unwrap())
-                            .with_extension("md"))
+                            .with_extension(extension))
                     }
                 }
             }
@@ -105,7 +187,7 @@ impl PathResolver {
                     let stem = input_path.file_stem().ok_or_else(|| {
                         ConfigError::InvalidPath("Input path has no file stem".to_string())
                     })?;
-                    Ok(input_dir.join(stem).with_extension("md"))
+                    Ok(input_dir.join(stem).with_extension(extension))
                 } else {
                     // Get the relative part of the input path from base_dir
                     let relative_input = input_path
@@ -119,22 +201,170 @@ impl PathResolver {
                     })?;
 
                     // Join with base_dir to create output path
-                    Ok(self.base_dir.join(parent).join(stem).with_extension("md"))
+                    Ok(self
+                        .base_dir
+                        .join(parent)
+                        .join(stem)
+                        .with_extension(extension))
                 }
             }
         }
     }
 }
 
+/// Resolves the base directory, expanding a leading `~`.
+pub fn resolve_base_dir(base_dir: &str) -> Result<PathBuf> {
+    Ok(PathResolver::new(base_dir)?.base_dir)
+}
+
+/// Resolves the output of a file found under the base directory, as if
+/// it had been given as the only input.
+pub fn resolve_job(
+    base_dir: &Path,
+    inline_output: bool,
+    input_path: &Path,
+    extension: &str,
+) -> Result<ConversionJob> {
+    let resolver = PathResolver {
+        base_dir: base_dir.to_path_buf(),
+    };
+    let output_path = resolver.resolve_output(None, input_path, inline_output, extension)?;
+
+    Ok(ConversionJob {
+        input_file: input_path.to_path_buf(),
+        output_file: output_path,
+    })
+}
+
+/// Resolves the inputs, which may include directories and glob patterns,
+/// and the output of each. With `combine`, every input is written to one
+/// output file, named after the first input unless given.
 pub fn resolve_paths(
     base_dir: &str,
     inline_output: bool,
-    input_file: &str,
+    input_files: &[String],
     output_file: Option<&str>,
-) -> Result<(PathBuf, PathBuf, PathBuf)> {
+    extension: &str,
+    combine: bool,
+) -> Result<(PathBuf, Vec<ConversionJob>)> {
     let resolver = PathResolver::new(base_dir)?;
-    let input_path = resolver.resolve_input(input_file)?;
-    let output_path = resolver.resolve_output(output_file, &input_path, inline_output)?;
 
-    Ok((resolver.base_dir, input_path, output_path))
+    let mut input_paths: Vec<PathBuf> = Vec::new();
+    for input_file in input_files {
+        for path in resolver.resolve_inputs(input_file)? {
+            if !input_paths.contains(&path) {
+                input_paths.push(path);
+            }
+        }
+    }
+
+    let Some(first_input) = input_paths.first() else {
+        return Err(ConfigError::MissingInputFile(format!(
+            "No chat files found in {}",
+            input_files.join(", ")
+        )));
+    };
+
+    if output_file.is_some() && input_paths.len() > 1 && !combine {
+        return Err(ConfigError::MultipleInputs(
+            "An output file can only be given for a single input, \
+             or with the openai or sharegpt format"
+                .to_string(),
+        ));
+    }
+
+    let combined_output = if combine {
+        Some(resolver.resolve_output(output_file, first_input, inline_output, extension)?)
+    } else {
+        None
+    };
+
+    let mut jobs = Vec::with_capacity(input_paths.len());
+    let mut inputs_by_output: HashMap<PathBuf, &PathBuf> = HashMap::new();
+    for input_path in &input_paths {
+        let output_path = match &combined_output {
+            Some(output_path) => output_path.clone(),
+            None => resolver.resolve_output(output_file, input_path, inline_output, extension)?,
+        };
+
+        if input_paths.contains(&output_path) {
+            return Err(ConfigError::InvalidPath(format!(
+                "Output file would overwrite an input file: {}",
+                output_path.display()
+            )));
+        }
+
+        // Inputs such as chat.json and chat.txt share a default output
+        if combined_output.is_none() {
+            if let Some(other) = inputs_by_output.insert(output_path.clone(), input_path) {
+                return Err(ConfigError::InvalidPath(format!(
+                    "{} and {} would both be written to {}",
+                    other.display(),
+                    input_path.display(),
+                    output_path.display()
+                )));
+            }
+        }
+
+        jobs.push(ConversionJob {
+            input_file: input_path.clone(),
+            output_file: output_path,
+        });
+    }
+
+    Ok((resolver.base_dir, jobs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory holding the given empty files.
+    fn dir_with(name: &str, files: &[&str]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aichatmd-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for file in files {
+            fs::write(dir.join(file), "").unwrap();
+        }
+        dir
+    }
+
+    fn resolve(dir: &Path, input: &str) -> Result<Vec<ConversionJob>> {
+        let base_dir = dir.to_str().unwrap();
+        resolve_paths(base_dir, false, &[input.to_string()], None, "md", false)
+            .map(|(_, jobs)| jobs)
+    }
+
+    #[test]
+    fn globs_only_match_chat_files() {
+        let dir = dir_with(
+            "glob",
+            &[
+                "a.json",
+                "a.md",
+                "b.chat.json",
+                "archive.db",
+                ".aichatmd-manifest.json",
+            ],
+        );
+
+        let jobs = resolve(&dir, "*").unwrap();
+        let inputs: Vec<_> = jobs.iter().map(|job| job.input_file.clone()).collect();
+        assert_eq!(inputs, [dir.join("a.json")]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn inputs_sharing_an_output_are_rejected() {
+        let dir = dir_with("shared", &["chat.json", "chat.txt"]);
+
+        assert!(matches!(
+            resolve(&dir, "*"),
+            Err(ConfigError::InvalidPath(_))
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
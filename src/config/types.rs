@@ -2,9 +2,10 @@ use super::ConfigError;
 use clap::{builder::PossibleValue, ValueEnum};
 use serde::Deserialize;
 
-#[derive(Clone, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ChatSource {
+    Auto,
     Claude,
     ChatGPT,
     DeepSeek,
@@ -12,11 +13,12 @@ pub enum ChatSource {
 
 impl ValueEnum for ChatSource {
     fn value_variants<'a>() -> &'a [Self] {
-        &[Self::Claude, Self::ChatGPT, Self::DeepSeek]
+        &[Self::Auto, Self::Claude, Self::ChatGPT, Self::DeepSeek]
     }
 
     fn to_possible_value(&self) -> Option<PossibleValue> {
         Some(match self {
+            Self::Auto => PossibleValue::new("auto"),
             Self::Claude => PossibleValue::new("claude"),
             Self::ChatGPT => PossibleValue::new("chatgpt"),
             Self::DeepSeek => PossibleValue::new("deepseek"),
@@ -32,6 +34,7 @@ impl ValueEnum for ChatSource {
         };
 
         match converted.as_str() {
+            "auto" => Ok(Self::Auto),
             "claude" => Ok(Self::Claude),
             "chatgpt" => Ok(Self::ChatGPT),
             "deepseek" => Ok(Self::DeepSeek),
@@ -45,6 +48,7 @@ impl std::str::FromStr for ChatSource {
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
+            "auto" => Ok(ChatSource::Auto),
             "claude" => Ok(ChatSource::Claude),
             "chatgpt" => Ok(ChatSource::ChatGPT),
             "deepseek" => Ok(ChatSource::DeepSeek),
@@ -59,6 +63,7 @@ impl std::str::FromStr for ChatSource {
 impl ChatSource {
     pub fn platform_name(&self) -> &'static str {
         match self {
+            ChatSource::Auto => "Auto",
             ChatSource::Claude => "Claude",
             ChatSource::ChatGPT => "ChatGPT",
             ChatSource::DeepSeek => "DeepSeek",
@@ -67,9 +72,82 @@ impl ChatSource {
 
     pub fn default_ai_name(&self) -> &'static str {
         match self {
+            ChatSource::Auto => "Assistant",
             ChatSource::Claude => "Claude",
             ChatSource::ChatGPT => "ChatGPT",
             ChatSource::DeepSeek => "DeepSeek",
         }
     }
 }
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    Markdown,
+    Html,
+    Json,
+    Obsidian,
+    OpenAi,
+    ShareGpt,
+}
+
+impl ValueEnum for OutputFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            Self::Markdown,
+            Self::Html,
+            Self::Json,
+            Self::Obsidian,
+            Self::OpenAi,
+            Self::ShareGpt,
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Self::Markdown => PossibleValue::new("markdown"),
+            Self::Html => PossibleValue::new("html"),
+            Self::Json => PossibleValue::new("json"),
+            Self::Obsidian => PossibleValue::new("obsidian"),
+            Self::OpenAi => PossibleValue::new("openai"),
+            Self::ShareGpt => PossibleValue::new("sharegpt"),
+        })
+    }
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "markdown" => Ok(OutputFormat::Markdown),
+            "html" => Ok(OutputFormat::Html),
+            "json" => Ok(OutputFormat::Json),
+            "obsidian" => Ok(OutputFormat::Obsidian),
+            "openai" => Ok(OutputFormat::OpenAi),
+            "sharegpt" => Ok(OutputFormat::ShareGpt),
+            _ => Err(ConfigError::OutputFormat(format!(
+                "Invalid output format: {}",
+                s
+            ))),
+        }
+    }
+}
+
+impl OutputFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Markdown | OutputFormat::Obsidian => "md",
+            OutputFormat::Html => "html",
+            // Keeps the output from replacing a `.json` input of the same name
+            OutputFormat::Json => "chat.json",
+            OutputFormat::OpenAi => "jsonl",
+            OutputFormat::ShareGpt => "sharegpt.json",
+        }
+    }
+
+    /// Dataset formats hold many conversations in one file.
+    pub fn is_dataset(&self) -> bool {
+        matches!(self, OutputFormat::OpenAi | OutputFormat::ShareGpt)
+    }
+}
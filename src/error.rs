@@ -10,6 +10,14 @@ pub enum AppError {
     Io(#[from] std::io::Error),
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
+    #[error("Zip error: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("Archive error: {0}")]
+    Archive(#[from] rusqlite::Error),
+    #[error("Watch error: {0}")]
+    Watch(#[from] notify_debouncer_mini::notify::Error),
+    #[error("Export error: {0}")]
+    Export(String),
 }
 
 pub type Result<T> = std::result::Result<T, AppError>;
@@ -0,0 +1,315 @@
+use crate::assets::{copy_images, Assets};
+use crate::config::{MarkdownConfig, OutputFormat};
+use crate::error::{AppError, Result};
+use crate::parser::{artifact_file_name, render_update, Block, BulkEntry, Conversation};
+use chrono_tz::Tz;
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File};
+use std::io::{ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+/// The file holding the conversations in an account export zip
+pub const EXPORT_CONVERSATIONS_FILE: &str = "conversations.json";
+
+/// The file holding the projects in a Claude account export
+pub const EXPORT_PROJECTS_FILE: &str = "projects.json";
+
+const MAX_TITLE_LENGTH: usize = 100;
+
+/// Outcome of converting an account export.
+pub struct BulkSummary {
+    pub converted: usize,
+    pub failed: Vec<BulkFailure>,
+}
+
+pub struct BulkFailure {
+    pub index: usize,
+    pub title: Option<String>,
+    pub error: String,
+}
+
+/// Reads the chat JSON from the input file. For an account export zip,
+/// the `conversations.json` inside it is read instead.
+pub fn read_input(path: &Path) -> Result<String> {
+    if path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+    {
+        read_zip_entry(path, EXPORT_CONVERSATIONS_FILE)?.ok_or_else(|| {
+            AppError::Export(format!(
+                "{} not found in {}",
+                EXPORT_CONVERSATIONS_FILE,
+                path.display()
+            ))
+        })
+    } else {
+        Ok(fs::read_to_string(path)?)
+    }
+}
+
+/// Reads the projects file that accompanies an account export, either from
+/// inside the export zip or from next to `conversations.json`.
+pub fn read_projects(input_path: &Path) -> Result<Option<String>> {
+    if input_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+    {
+        return read_zip_entry(input_path, EXPORT_PROJECTS_FILE);
+    }
+
+    let projects_path = input_path.with_file_name(EXPORT_PROJECTS_FILE);
+    if projects_path.exists() {
+        Ok(Some(fs::read_to_string(projects_path)?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Reads a file from a zip by name, wherever it sits in the folder structure.
+pub fn read_zip_entry(path: &Path, name: &str) -> Result<Option<String>> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let matches = Path::new(entry.name())
+            .file_name()
+            .is_some_and(|file_name| file_name == name);
+        if matches {
+            let mut content = String::with_capacity(entry.size() as usize);
+            entry.read_to_string(&mut content)?;
+            return Ok(Some(content));
+        }
+    }
+
+    Ok(None)
+}
+
+fn sanitize_title(title: &str) -> String {
+    let cleaned: String = title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => ' ',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect();
+    let collapsed = cleaned.split_whitespace().collect::<Vec<_>>().join(" ");
+    let truncated: String = collapsed.chars().take(MAX_TITLE_LENGTH).collect();
+    let trimmed = truncated.trim_end_matches(['.', ' ']);
+
+    if trimmed.is_empty() {
+        "Untitled".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// The directory an account export is written to, named after the
+/// output file without its extension.
+pub fn bulk_output_dir(output_file: &Path, extension: &str) -> PathBuf {
+    let suffix = format!(".{}", extension);
+    match output_file
+        .to_str()
+        .and_then(|path| path.strip_suffix(&suffix))
+    {
+        Some(dir) => PathBuf::from(dir),
+        None => output_file.with_extension(""),
+    }
+}
+
+/// Builds a file name for a conversation from its date and title,
+/// e.g. `2024-05-01 Planning the trip`, without an extension.
+pub fn conversation_file_stem(conversation: &Conversation, timezone: &str) -> String {
+    let timezone: Tz = timezone.parse().unwrap_or(Tz::UTC);
+    format!(
+        "{} {}",
+        conversation
+            .created_at
+            .with_timezone(&timezone)
+            .format("%Y-%m-%d"),
+        sanitize_title(&conversation.title)
+    )
+}
+
+/// Name of the directory next to an output file that its artifacts are
+/// written to, e.g. `chat_artifacts` for `chat.md`.
+pub fn artifact_dir_name(output_file: &Path) -> String {
+    let stem = output_file
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+    format!("{}_artifacts", stem)
+}
+
+/// Writes the final version of every artifact on the exported path to
+/// `dir` as `<id>.<ext>`, returning how many were written. With
+/// `branches`, the final version of each artifact within an alternate
+/// branch is written as `<id>.b<n>.<ext>` as well. Artifacts without an
+/// id stay inline and are not written.
+pub fn write_artifacts(conversation: &Conversation, branches: bool, dir: &Path) -> Result<usize> {
+    let paths = std::iter::once((None, &conversation.turns)).chain(
+        conversation
+            .branches
+            .iter()
+            .enumerate()
+            .filter(|_| branches)
+            .map(|(i, branch)| (Some(i + 1), &branch.turns)),
+    );
+
+    let mut latest = BTreeMap::new();
+    for (branch, turns) in paths {
+        for block in turns.iter().flat_map(|turn| &turn.blocks) {
+            if let Block::Artifact {
+                id: Some(id),
+                kind,
+                language,
+                content,
+                ..
+            } = block
+            {
+                let file_name =
+                    artifact_file_name(id, branch, kind.as_deref(), language.as_deref());
+                latest.insert(file_name, content);
+            }
+        }
+    }
+
+    if !latest.is_empty() {
+        fs::create_dir_all(dir)?;
+    }
+    for (file_name, content) in &latest {
+        fs::write(dir.join(file_name), content)?;
+    }
+
+    Ok(latest.len())
+}
+
+/// Writes a conversation to its output file. In update mode an earlier
+/// export is appended to instead of replaced, and with `artifact_files`
+/// the artifacts of the Markdown formats are written next to it. Returns
+/// false if the file already held every turn and was left alone.
+pub fn write_conversation(
+    conversation: &Conversation,
+    config: &MarkdownConfig,
+    output_file: &Path,
+    on_turn: impl FnMut(),
+) -> Result<bool> {
+    let links_artifacts = config.artifact_files
+        && matches!(
+            config.format,
+            OutputFormat::Markdown | OutputFormat::Obsidian
+        );
+    let artifact_config;
+    let config = if links_artifacts {
+        artifact_config = MarkdownConfig {
+            artifact_dir: Some(artifact_dir_name(output_file)),
+            ..config.clone()
+        };
+        &artifact_config
+    } else {
+        config
+    };
+
+    let existing = match fs::read_to_string(output_file) {
+        Ok(existing) if config.update => Some(existing),
+        Err(err) if config.update && err.kind() != ErrorKind::NotFound => return Err(err.into()),
+        _ => None,
+    };
+
+    let output = render_update(conversation, config, existing.as_deref(), on_turn)?;
+    if let Some(output) = &output {
+        fs::write(output_file, output)?;
+    }
+
+    if let Some(dir_name) = &config.artifact_dir {
+        write_artifacts(
+            conversation,
+            config.branches,
+            &output_file.with_file_name(dir_name),
+        )?;
+    }
+
+    Ok(output.is_some())
+}
+
+/// Writes each successfully parsed conversation to its own file in
+/// `output_dir`, collecting failures instead of stopping at the first one.
+/// Images found in the export's `assets` are copied next to each file.
+/// `on_written` is called with every conversation and the file it was
+/// written to, and an error from it counts as a failure of that
+/// conversation. `on_entry` is called once per entry so callers can
+/// report progress.
+pub fn write_bulk(
+    entries: Vec<BulkEntry>,
+    output_dir: &Path,
+    config: &MarkdownConfig,
+    assets: &Assets,
+    mut on_written: impl FnMut(&Conversation, &Path) -> Result<()>,
+    mut on_entry: impl FnMut(),
+) -> Result<BulkSummary> {
+    fs::create_dir_all(output_dir)?;
+
+    let mut used_names = HashSet::new();
+    let mut summary = BulkSummary {
+        converted: 0,
+        failed: Vec::new(),
+    };
+
+    for entry in entries {
+        on_entry();
+
+        let mut conversation = match entry.conversation {
+            Ok(conversation) => conversation,
+            Err(err) => {
+                summary.failed.push(BulkFailure {
+                    index: entry.index,
+                    title: entry.title,
+                    error: err.to_string(),
+                });
+                continue;
+            }
+        };
+
+        // Conversations often share a title, so number the duplicates
+        let extension = config.format.extension();
+        let stem = conversation_file_stem(&conversation, &config.timezone);
+        let mut unique_name = format!("{}.{}", stem, extension);
+        let mut counter = 2;
+        while !used_names.insert(unique_name.clone()) {
+            unique_name = format!("{} ({}).{}", stem, counter, extension);
+            counter += 1;
+        }
+
+        let output_file = output_dir.join(&unique_name);
+        copy_images(
+            &mut conversation,
+            assets,
+            &output_file,
+            config.format == OutputFormat::Html,
+        )?;
+        match write_conversation(&conversation, config, &output_file, || {}) {
+            Ok(_) => {}
+            Err(AppError::Parser(err)) => {
+                summary.failed.push(BulkFailure {
+                    index: entry.index,
+                    title: entry.title,
+                    error: err.to_string(),
+                });
+                continue;
+            }
+            Err(err) => return Err(err),
+        }
+        if let Err(err) = on_written(&conversation, &output_file) {
+            summary.failed.push(BulkFailure {
+                index: entry.index,
+                title: entry.title,
+                error: err.to_string(),
+            });
+            continue;
+        }
+        summary.converted += 1;
+    }
+
+    Ok(summary)
+}
@@ -0,0 +1,25 @@
+//! Converts AI chat exports from Claude, ChatGPT and DeepSeek into Markdown
+//! or self-contained HTML.
+//!
+//! The [`convert`] function works entirely in memory: it takes the raw JSON
+//! and returns the rendered document, without reading or writing files or
+//! printing progress.
+
+pub mod archive;
+pub mod assets;
+pub mod config;
+pub mod error;
+pub mod export;
+pub mod manifest;
+pub mod parser;
+
+pub use config::{ChatSource, MarkdownConfig, OutputFormat};
+pub use error::{AppError, Result};
+pub use parser::{parse_conversation, render, render_markdown, Conversation};
+
+/// Converts a chat in JSON form from the given platform into the format
+/// selected by `config.format`.
+pub fn convert(json: &str, source: ChatSource, config: &MarkdownConfig) -> Result<String> {
+    let conversation = parse_conversation(json, source, config)?;
+    Ok(render(&conversation, config)?)
+}
@@ -1,16 +1,593 @@
-mod config;
-mod error;
-mod parser;
-
+use chrono_tz::Tz;
 use clap::Parser;
+use indicatif::{ProgressBar, ProgressStyle};
+use notify_debouncer_mini::notify::RecursiveMode;
+use notify_debouncer_mini::{new_debouncer, DebouncedEventKind};
+use rayon::prelude::*;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::time::{Duration, SystemTime};
 
-use crate::config::{build_config, ChatSource, CliArgs};
-use crate::error::Result;
-use crate::parser::{
-    parse_chatgpt_to_markdown, parse_claude_to_markdown, parse_deepseek_to_markdown,
+use ai_chat_md::archive::{Archive, Upsert};
+use ai_chat_md::assets::{asset_dir_name, copy_images, Assets};
+use ai_chat_md::config::{
+    build_config, build_search_config, is_watched_file, resolve_job, ChatSource, CliArgs,
+    ConversionJob, MarkdownConfig, OutputFormat, RunConfig, SearchConfig, WatchConfig,
+};
+use ai_chat_md::error::{AppError, Result};
+use ai_chat_md::export::{
+    artifact_dir_name, bulk_output_dir, read_input, read_projects, write_bulk, write_conversation,
+};
+use ai_chat_md::manifest::{Fingerprint, Manifest, MANIFEST_FILE};
+use ai_chat_md::parser::{
+    apply_project_names, is_bulk_export, parse_bulk_value, parse_conversation_value,
+    parse_projects, render_dataset, Conversation, ParseError,
 };
 
+fn progress_bar(len: usize) -> ProgressBar {
+    let progress = ProgressBar::new(len as u64);
+    progress.set_style(
+        ProgressStyle::default_bar()
+            .progress_chars("##-")
+            .template("[{bar:40}] {pos}/{len} ({percent}%)")
+            .unwrap(),
+    );
+    progress
+}
+
+/// Loads converted conversations into the archive, if one was requested.
+/// Conversions run in parallel, so the connection sits behind a lock.
+struct Archiver {
+    archive: Option<Mutex<Archive>>,
+    inserted: AtomicUsize,
+    updated: AtomicUsize,
+}
+
+impl Archiver {
+    fn open(run_config: &RunConfig) -> Result<Self> {
+        let archive = match &run_config.archive_file {
+            Some(path) => {
+                println!("Archive: {}", path.display());
+                Some(Mutex::new(Archive::open(path)?))
+            }
+            None => None,
+        };
+        Ok(Self {
+            archive,
+            inserted: AtomicUsize::new(0),
+            updated: AtomicUsize::new(0),
+        })
+    }
+
+    fn add(&self, conversation: &Conversation, output_file: &Path) -> Result<()> {
+        if let Some(archive) = &self.archive {
+            let archive = archive.lock().unwrap_or_else(|err| err.into_inner());
+            match archive.upsert(conversation, Some(output_file))? {
+                Upsert::Inserted => self.inserted.fetch_add(1, Ordering::Relaxed),
+                Upsert::Updated => self.updated.fetch_add(1, Ordering::Relaxed),
+            };
+        }
+        Ok(())
+    }
+
+    fn report(&self) {
+        if self.archive.is_some() {
+            println!(
+                "Archived {} new and {} updated conversations",
+                self.inserted.load(Ordering::Relaxed),
+                self.updated.load(Ordering::Relaxed)
+            );
+        }
+    }
+}
+
+/// Where a conversion reports its progress. A single input gets its own
+/// progress bar and messages; in a batch, files share one bar and only
+/// warnings are printed, prefixed with the file they came from.
+#[derive(Clone, Copy)]
+enum Reporter<'a> {
+    Single,
+    Batch(&'a ProgressBar),
+}
+
+impl Reporter<'_> {
+    fn progress(&self, len: usize) -> ProgressBar {
+        match self {
+            Reporter::Single => progress_bar(len),
+            Reporter::Batch(_) => ProgressBar::hidden(),
+        }
+    }
+
+    /// Details only worth showing for a single input.
+    fn info(&self, message: String) {
+        if let Reporter::Single = self {
+            println!("{}", message);
+        }
+    }
+
+    fn warn(&self, input_file: &Path, message: String) {
+        match self {
+            Reporter::Single => println!("{}", message),
+            Reporter::Batch(progress) => {
+                progress.println(format!("{}: {}", input_file.display(), message))
+            }
+        }
+    }
+}
+
+/// The run configuration for one input file.
+fn job_config(job: &ConversionJob, run_config: &RunConfig) -> MarkdownConfig {
+    MarkdownConfig {
+        source_file: job
+            .input_file
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned()),
+        ..run_config.markdown.clone()
+    }
+}
+
+fn convert_single(
+    value: Value,
+    job: &ConversionJob,
+    run_config: &RunConfig,
+    archiver: &Archiver,
+    reporter: Reporter,
+) -> Result<PathBuf> {
+    let config = job_config(job, run_config);
+
+    // Parse
+    let mut conversation = parse_conversation_value(value, run_config.chat_source, &config)?;
+    if run_config.chat_source == ChatSource::Auto {
+        reporter.info(format!("Detected: {}", conversation.source.platform_name()));
+    }
+    for warning in &conversation.warnings {
+        reporter.warn(&job.input_file, format!("Warning: {}", warning));
+    }
+
+    // Copy the images the export includes, then render in the chosen
+    // format and write to file
+    copy_images(
+        &mut conversation,
+        &Assets::new(&job.input_file),
+        &job.output_file,
+        config.format == OutputFormat::Html,
+    )?;
+    let progress = reporter.progress(conversation.turns.len());
+    let written = write_conversation(&conversation, &config, &job.output_file, || progress.inc(1))?;
+    progress.finish();
+    if !written {
+        reporter.info("No new messages since the last export".to_string());
+    }
+    archiver.add(&conversation, &job.output_file)?;
+
+    Ok(job.output_file.clone())
+}
+
+fn convert_bulk(
+    value: Value,
+    job: &ConversionJob,
+    run_config: &RunConfig,
+    archiver: &Archiver,
+    reporter: Reporter,
+) -> Result<PathBuf> {
+    // Account exports are written to a directory named after the output file
+    let output_dir = bulk_output_dir(&job.output_file, run_config.markdown.format.extension());
+    reporter.info(format!(
+        "Account export, writing to: {}",
+        output_dir.display()
+    ));
+
+    // Every conversation keeps its own title and current path
+    let markdown_config = MarkdownConfig {
+        title: None,
+        leaf: None,
+        ..job_config(job, run_config)
+    };
+
+    let mut entries = parse_bulk_value(value, run_config.chat_source, &markdown_config)?;
+
+    // Claude exports list project names separately
+    if let Some(projects_json) = read_projects(&job.input_file)? {
+        match parse_projects(&projects_json) {
+            Ok(projects) => apply_project_names(&mut entries, &projects),
+            Err(err) => reporter.warn(
+                &job.input_file,
+                format!("Warning: Could not read projects: {}", err),
+            ),
+        }
+    }
+    let total = entries.len();
+    let progress = reporter.progress(total);
+    let summary = write_bulk(
+        entries,
+        &output_dir,
+        &markdown_config,
+        &Assets::new(&job.input_file),
+        |conversation, output_file| {
+            for warning in &conversation.warnings {
+                reporter.warn(
+                    &job.input_file,
+                    format!("Warning: {}: {}", conversation.title, warning),
+                );
+            }
+            archiver.add(conversation, output_file)
+        },
+        || progress.inc(1),
+    )?;
+    progress.finish();
+
+    for failure in &summary.failed {
+        reporter.warn(
+            &job.input_file,
+            format!(
+                "Failed: #{} {}: {}",
+                failure.index + 1,
+                failure.title.as_deref().unwrap_or("(untitled)"),
+                failure.error
+            ),
+        );
+    }
+    reporter.info(format!(
+        "Converted {} of {} conversations ({} failed)",
+        summary.converted,
+        total,
+        summary.failed.len()
+    ));
+
+    Ok(output_dir)
+}
+
+/// What became of one input file.
+enum Outcome {
+    /// Written to the given file, or directory for an account export.
+    Converted(PathBuf),
+    /// Left alone, as the manifest shows its output is up to date.
+    Unchanged,
+}
+
+/// Converts one input file, which may hold a single chat or a whole
+/// account export, unless its output is already up to date.
+fn convert_job(
+    job: &ConversionJob,
+    run_config: &RunConfig,
+    archiver: &Archiver,
+    manifest: &Mutex<Manifest>,
+    reporter: Reporter,
+) -> Result<Outcome> {
+    let fingerprint = Fingerprint::new(
+        &job.input_file,
+        run_config.chat_source,
+        &job_config(job, run_config),
+    )?;
+    let is_current = manifest
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .is_current(&job.output_file, &fingerprint);
+    if is_current && !run_config.force {
+        return Ok(Outcome::Unchanged);
+    }
+
+    if let Some(parent) = job.output_file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let json_content = read_input(&job.input_file)?;
+    let value: Value = serde_json::from_str(&json_content)?;
+
+    let written = if is_bulk_export(&value) {
+        convert_bulk(value, job, run_config, archiver, reporter)?
+    } else {
+        convert_single(value, job, run_config, archiver, reporter)?
+    };
+
+    manifest
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .record(&job.input_file, &job.output_file, &written, fingerprint);
+
+    Ok(Outcome::Converted(written))
+}
+
+/// Whether a failed input was simply not a chat export, such as an
+/// unrelated JSON or text file picked up from a directory.
+fn is_not_a_chat(err: &AppError) -> bool {
+    matches!(
+        err,
+        AppError::Json(_) | AppError::Parser(ParseError::UnknownSource(_))
+    )
+}
+
+/// Converts every input in parallel behind one progress bar, then
+/// summarizes how each file went.
+fn convert_batch(run_config: &RunConfig, archiver: &Archiver, manifest: &Mutex<Manifest>) {
+    let progress = progress_bar(run_config.jobs.len());
+    let reporter = Reporter::Batch(&progress);
+
+    let results: Vec<_> = run_config
+        .jobs
+        .par_iter()
+        .map(|job| {
+            let result = convert_job(job, run_config, archiver, manifest, reporter);
+            progress.inc(1);
+            (job, result)
+        })
+        .collect();
+    progress.finish();
+
+    let mut converted = 0;
+    let mut unchanged = 0;
+    let mut skipped = 0;
+    let mut failed = Vec::new();
+    for (job, result) in results {
+        match result {
+            Ok(Outcome::Converted(_)) => converted += 1,
+            Ok(Outcome::Unchanged) => unchanged += 1,
+            Err(err) if is_not_a_chat(&err) => skipped += 1,
+            Err(err) => failed.push((job, err)),
+        }
+    }
+
+    for (job, err) in &failed {
+        println!("Failed: {}: {}", job.input_file.display(), err);
+    }
+    println!(
+        "Converted {} files, {} unchanged, skipped {} that were not chats, {} failed",
+        converted,
+        unchanged,
+        skipped,
+        failed.len()
+    );
+}
+
+/// Reads the conversations of one dataset input, which may be a single
+/// chat or a whole account export.
+fn read_dataset_input(
+    input_file: &Path,
+    run_config: &RunConfig,
+    config: &MarkdownConfig,
+    progress: &ProgressBar,
+) -> Result<Vec<Conversation>> {
+    let value: Value = serde_json::from_str(&read_input(input_file)?)?;
+
+    if !is_bulk_export(&value) {
+        let conversation = parse_conversation_value(value, run_config.chat_source, config)?;
+        return Ok(vec![conversation]);
+    }
+
+    // Every conversation keeps its own current path
+    let bulk_config = MarkdownConfig {
+        leaf: None,
+        ..config.clone()
+    };
+    let mut conversations = Vec::new();
+    for entry in parse_bulk_value(value, run_config.chat_source, &bulk_config)? {
+        match entry.conversation {
+            Ok(conversation) => conversations.push(conversation),
+            Err(err) => progress.println(format!(
+                "Failed: {} #{} {}: {}",
+                input_file.display(),
+                entry.index + 1,
+                entry.title.as_deref().unwrap_or("(untitled)"),
+                err
+            )),
+        }
+    }
+    Ok(conversations)
+}
+
+fn convert_dataset(run_config: &RunConfig, archiver: &Archiver) -> Result<()> {
+    // Every job shares the one dataset file
+    let output_file = &run_config.jobs[0].output_file;
+    if let Some(parent) = output_file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // A leaf id only makes sense for a single conversation
+    let config = if run_config.jobs.len() > 1 {
+        MarkdownConfig {
+            leaf: None,
+            ..run_config.markdown.clone()
+        }
+    } else {
+        run_config.markdown.clone()
+    };
+
+    // Inputs are read in parallel but keep their order in the dataset
+    let progress = progress_bar(run_config.jobs.len());
+    let results: Vec<_> = run_config
+        .jobs
+        .par_iter()
+        .map(|job| {
+            let result = read_dataset_input(&job.input_file, run_config, &config, &progress);
+            progress.inc(1);
+            (job, result)
+        })
+        .collect();
+    progress.finish();
+
+    let mut conversations = Vec::new();
+    let mut failed = 0;
+    for (job, result) in results {
+        match result {
+            Ok(mut read) => {
+                for conversation in &read {
+                    for warning in &conversation.warnings {
+                        println!("{}: Warning: {}", job.input_file.display(), warning);
+                    }
+                }
+                conversations.append(&mut read);
+            }
+            Err(err) => {
+                println!("Failed: {}: {}", job.input_file.display(), err);
+                failed += 1;
+            }
+        }
+    }
+
+    let conversations: Vec<&Conversation> = conversations.iter().collect();
+    fs::write(output_file, render_dataset(&conversations, &config)?)?;
+    for conversation in &conversations {
+        archiver.add(conversation, output_file)?;
+    }
+    println!(
+        "Added {} conversations from {} of {} files ({} failed)",
+        conversations.len(),
+        run_config.jobs.len() - failed,
+        run_config.jobs.len(),
+        failed
+    );
+
+    Ok(())
+}
+
+/// How long a file must go unchanged before it is converted, so a chat
+/// is not read while the browser is still writing it.
+const SETTLE_TIME: Duration = Duration::from_secs(2);
+
+/// Converts chats as they are saved under the base directory, until the
+/// process is stopped.
+/// Paths under the watched directory that this run writes to, which are
+/// never taken for chats saved there.
+struct OwnPaths(HashSet<PathBuf>);
+
+impl OwnPaths {
+    fn new(run_config: &RunConfig, watch_config: &WatchConfig) -> Self {
+        let mut paths = HashSet::from([watch_config.base_dir.join(MANIFEST_FILE)]);
+        paths.extend(run_config.archive_file.clone());
+        Self(paths)
+    }
+
+    /// Adds a written output, which is a directory for an account export,
+    /// along with the directories of its artifacts and images.
+    fn add_output(&mut self, written: &Path) {
+        self.0.insert(written.to_path_buf());
+        self.0
+            .insert(written.with_file_name(artifact_dir_name(written)));
+        self.0
+            .insert(written.with_file_name(asset_dir_name(written)));
+    }
+
+    fn contains(&self, path: &Path) -> bool {
+        self.0.iter().any(|own| path.starts_with(own))
+    }
+}
+
+fn watch(
+    run_config: &RunConfig,
+    watch_config: &WatchConfig,
+    archiver: &Archiver,
+    manifest: &Mutex<Manifest>,
+) -> Result<()> {
+    let (sender, receiver) = mpsc::channel();
+    let mut debouncer = new_debouncer(SETTLE_TIME, sender)?;
+    debouncer
+        .watcher()
+        .watch(&watch_config.base_dir, RecursiveMode::Recursive)?;
+
+    println!(
+        "Watching {} for new chats, press Ctrl+C to stop",
+        watch_config.base_dir.display()
+    );
+
+    let mut converted: HashMap<PathBuf, SystemTime> = HashMap::new();
+    let mut own_paths = OwnPaths::new(run_config, watch_config);
+    for result in receiver {
+        let events = match result {
+            Ok(events) => events,
+            Err(err) => {
+                println!("Warning: {}", err);
+                continue;
+            }
+        };
+
+        // Files still being written are picked up once they settle
+        for event in events {
+            if event.kind != DebouncedEventKind::Any
+                || !is_watched_file(&event.path)
+                || own_paths.contains(&event.path)
+            {
+                continue;
+            }
+
+            // Reading a file raises events too, so only content that
+            // changed since it was last converted is picked up
+            let Ok(modified) = fs::metadata(&event.path).and_then(|meta| meta.modified()) else {
+                continue;
+            };
+            if converted.get(&event.path) == Some(&modified) {
+                continue;
+            }
+            converted.insert(event.path.clone(), modified);
+
+            let job = match resolve_job(
+                &watch_config.base_dir,
+                watch_config.inline_output,
+                &event.path,
+                run_config.markdown.format.extension(),
+            ) {
+                Ok(job) => job,
+                Err(err) => {
+                    println!("\nFailed: {}: {}", event.path.display(), err);
+                    continue;
+                }
+            };
+            println!("\nInput: {}", job.input_file.display());
+            match convert_job(&job, run_config, archiver, manifest, Reporter::Single) {
+                Ok(Outcome::Converted(written)) => {
+                    println!("Output: {}", written.display());
+                    own_paths.add_output(&written);
+                }
+                Ok(Outcome::Unchanged) => println!("Unchanged since it was last converted"),
+                Err(err) if is_not_a_chat(&err) => println!("Skipped: not a chat"),
+                Err(err) => println!("Failed: {}", err),
+            }
+            if let Err(err) = manifest
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .save()
+            {
+                println!("Warning: Could not save the manifest: {}", err);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn search(search_config: &SearchConfig) -> Result<()> {
+    if !search_config.archive_file.exists() {
+        println!(
+            "Archive not found: {}",
+            search_config.archive_file.display()
+        );
+        return Ok(());
+    }
+
+    let archive = Archive::open(&search_config.archive_file)?;
+    let hits = archive.search(&search_config.query, search_config.limit)?;
+
+    for hit in &hits {
+        println!(
+            "\n{} ({}, {})",
+            hit.title,
+            hit.platform,
+            hit.created_at.as_deref().unwrap_or("Unknown Time")
+        );
+        println!("  {}: {}", hit.role, hit.snippet.replace('\n', " "));
+        match &hit.output_file {
+            Some(output_file) => println!("  {}", output_file),
+            None => println!("  (conversation {})", hit.conversation_id),
+        }
+    }
+    println!("\nFound {} matching messages", hits.len());
+
+    Ok(())
+}
+
 fn main() -> Result<()> {
     // Setup app metadata
     let app_name = env!("APP_NAME");
@@ -23,46 +600,53 @@ fn main() -> Result<()> {
     // App banner
     println!("{} v{} (build {})", app_name, app_version, app_build);
 
-    // Get configuration
-    let markdown_config = build_config(&cli_args)?;
-
-    // Create output directory
-    if let Some(parent) = markdown_config.output_file.parent() {
-        fs::create_dir_all(parent)?;
+    // Searching only reads the archive
+    if let Some(search_config) = build_search_config(&cli_args)? {
+        return search(&search_config);
     }
 
-    println!("Input: {}", markdown_config.input_file.display());
-    println!("Output: {}", markdown_config.output_file.display());
-
-    // Read input file
-    let json_content = fs::read_to_string(&markdown_config.input_file)?;
-
-    match markdown_config.chat_source {
-        ChatSource::Claude => {
-            // Parse
-            let chat = serde_json::from_str(&json_content)?;
-
-            // Convert to markdown and write to file
-            let markdown = parse_claude_to_markdown(&chat, &markdown_config)?;
-            fs::write(&markdown_config.output_file, markdown)?;
-        }
-        ChatSource::ChatGPT => {
-            // Parse
-            let chat = serde_json::from_str(&json_content)?;
+    // Get configuration
+    let run_config = build_config(&cli_args)?;
+    if run_config.markdown.timezone.parse::<Tz>().is_err() {
+        println!(
+            "Warning: Invalid timezone '{}', falling back to UTC",
+            run_config.markdown.timezone
+        );
+    }
+    let archiver = Archiver::open(&run_config)?;
+    let manifest = Mutex::new(Manifest::load(&run_config.base_dir));
 
-            // Convert to markdown and write to file
-            let markdown = parse_chatgpt_to_markdown(&chat, &markdown_config)?;
-            fs::write(&markdown_config.output_file, markdown)?;
+    // Watch mode finds its inputs as they are saved, while datasets
+    // combine every input into the one output file
+    if let Some(watch_config) = &run_config.watch {
+        watch(&run_config, watch_config, &archiver, &manifest)?;
+    } else if run_config.markdown.format.is_dataset() {
+        for job in &run_config.jobs {
+            println!("Input: {}", job.input_file.display());
         }
-        ChatSource::DeepSeek => {
-            // Parse
-            let chat = serde_json::from_str(&json_content)?;
-
-            // Convert to markdown and write to file
-            let markdown = parse_deepseek_to_markdown(&chat, &markdown_config)?;
-            fs::write(&markdown_config.output_file, markdown)?;
+        println!("Output: {}", run_config.jobs[0].output_file.display());
+        convert_dataset(&run_config, &archiver)?;
+    } else if let [job] = run_config.jobs.as_slice() {
+        println!("Input: {}", job.input_file.display());
+        println!("Output: {}", job.output_file.display());
+        if let Outcome::Unchanged =
+            convert_job(job, &run_config, &archiver, &manifest, Reporter::Single)?
+        {
+            println!("Unchanged since it was last converted, use --force to rebuild");
         }
+        manifest
+            .into_inner()
+            .unwrap_or_else(|err| err.into_inner())
+            .save()?;
+    } else {
+        println!("Converting {} files", run_config.jobs.len());
+        convert_batch(&run_config, &archiver, &manifest);
+        manifest
+            .into_inner()
+            .unwrap_or_else(|err| err.into_inner())
+            .save()?;
     }
+    archiver.report();
 
     println!("Done.");
 
@@ -0,0 +1,115 @@
+use crate::config::{ChatSource, MarkdownConfig};
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the manifest kept in the base directory.
+pub const MANIFEST_FILE: &str = ".aichatmd-manifest.json";
+
+/// What an output was built from. An output is only rebuilt when one of
+/// these changes.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    pub input_hash: String,
+    pub config_hash: String,
+    pub tool_version: String,
+}
+
+impl Fingerprint {
+    /// Hashes the input file as stored, and the options it is converted
+    /// with. Options are hashed through their debug form, which changes
+    /// whenever an option is added, so new options rebuild everything.
+    pub fn new(
+        input_file: &Path,
+        chat_source: ChatSource,
+        config: &MarkdownConfig,
+    ) -> Result<Self> {
+        let input = fs::read(input_file)?;
+        let options = format!("{:?} {:?}", chat_source, config);
+
+        Ok(Self {
+            input_hash: format!("{:x}", Sha256::digest(&input)),
+            config_hash: format!("{:x}", Sha256::digest(options.as_bytes())),
+            tool_version: env!("APP_VERSION").to_string(),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Entry {
+    input: String,
+    output: String,
+    #[serde(flatten)]
+    fingerprint: Fingerprint,
+}
+
+/// Records the fingerprint of every output written under the base
+/// directory, so re-running over the same folder skips unchanged inputs.
+/// Entries are keyed by output path, relative to the base directory.
+pub struct Manifest {
+    path: PathBuf,
+    base_dir: PathBuf,
+    outputs: BTreeMap<String, Entry>,
+}
+
+impl Manifest {
+    /// Loads the manifest from the base directory. A missing or damaged
+    /// manifest starts empty, which only costs a full rebuild.
+    pub fn load(base_dir: &Path) -> Self {
+        let path = base_dir.join(MANIFEST_FILE);
+        let outputs = fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        Self {
+            path,
+            base_dir: base_dir.to_path_buf(),
+            outputs,
+        }
+    }
+
+    fn key(&self, path: &Path) -> String {
+        path.strip_prefix(&self.base_dir)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    }
+
+    /// Returns true if the output was built from the same input and
+    /// options, and is still on disk.
+    pub fn is_current(&self, output_file: &Path, fingerprint: &Fingerprint) -> bool {
+        self.outputs
+            .get(&self.key(output_file))
+            .is_some_and(|entry| {
+                entry.fingerprint == *fingerprint && self.base_dir.join(&entry.output).exists()
+            })
+    }
+
+    /// Records a conversion. `written` is where the output actually went,
+    /// which is a directory for account exports.
+    pub fn record(
+        &mut self,
+        input_file: &Path,
+        output_file: &Path,
+        written: &Path,
+        fingerprint: Fingerprint,
+    ) {
+        let entry = Entry {
+            input: self.key(input_file),
+            output: self.key(written),
+            fingerprint,
+        };
+        self.outputs.insert(self.key(output_file), entry);
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let mut json = serde_json::to_string_pretty(&self.outputs)?;
+        json.push('\n');
+        fs::write(&self.path, json)?;
+        Ok(())
+    }
+}
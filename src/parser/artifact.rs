@@ -0,0 +1,92 @@
+/// Maps an artifact's MIME type, or for code its language, to the
+/// extension it is saved with.
+fn extension(kind: Option<&str>, language: Option<&str>) -> &'static str {
+    let language = language.map(str::to_ascii_lowercase);
+
+    match (kind, language.as_deref()) {
+        (Some("text/markdown"), _) => "md",
+        (Some("text/html"), _) => "html",
+        (Some("image/svg+xml"), _) => "svg",
+        (Some("application/vnd.ant.mermaid"), _) => "mmd",
+        (Some("application/vnd.ant.react"), Some("typescript" | "tsx")) => "tsx",
+        (Some("application/vnd.ant.react"), _) => "jsx",
+        (_, Some(language)) => match language {
+            "python" | "py" => "py",
+            "javascript" | "js" => "js",
+            "typescript" | "ts" => "ts",
+            "jsx" => "jsx",
+            "tsx" => "tsx",
+            "rust" | "rs" => "rs",
+            "go" | "golang" => "go",
+            "java" => "java",
+            "kotlin" => "kt",
+            "swift" => "swift",
+            "c" => "c",
+            "cpp" | "c++" => "cpp",
+            "csharp" | "c#" | "cs" => "cs",
+            "ruby" | "rb" => "rb",
+            "php" => "php",
+            "bash" | "shell" | "sh" | "zsh" => "sh",
+            "powershell" => "ps1",
+            "sql" => "sql",
+            "html" => "html",
+            "css" => "css",
+            "scss" => "scss",
+            "json" => "json",
+            "yaml" | "yml" => "yaml",
+            "toml" => "toml",
+            "xml" => "xml",
+            "markdown" | "md" => "md",
+            "r" => "r",
+            "scala" => "scala",
+            "lua" => "lua",
+            "perl" => "pl",
+            "haskell" => "hs",
+            "dart" => "dart",
+            "latex" | "tex" => "tex",
+            _ => "txt",
+        },
+        _ => "txt",
+    }
+}
+
+/// File name an artifact is saved under: its id, made safe for the file
+/// system, with an extension from its type or language. The version of
+/// an alternate branch is saved apart as `<id>.b<n>.<ext>`, `n` being the
+/// branch number.
+pub fn artifact_file_name(
+    id: &str,
+    branch: Option<usize>,
+    kind: Option<&str>,
+    language: Option<&str>,
+) -> String {
+    let stem: String = id
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    match branch {
+        Some(branch) => format!("{}.b{}.{}", stem, branch, extension(kind, language)),
+        None => format!("{}.{}", stem, extension(kind, language)),
+    }
+}
+
+/// Heading label of an artifact, noting when a command changed an
+/// existing one rather than creating it.
+pub(crate) fn artifact_label(
+    id: Option<&str>,
+    title: Option<&str>,
+    command: Option<&str>,
+) -> Option<String> {
+    let label = title.or(id)?;
+    Some(match command {
+        Some("update") => format!("{} (updated)", label),
+        Some("rewrite") => format!("{} (rewritten)", label),
+        _ => label.to_string(),
+    })
+}
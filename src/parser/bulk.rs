@@ -0,0 +1,78 @@
+use crate::config::{ChatSource, MarkdownConfig};
+use crate::parser::conversation::Conversation;
+use crate::parser::convert::parse_conversation_value;
+use crate::parser::error::{ParseError, Result};
+use crate::parser::model::ClaudeProject;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// One conversation from an account export, converted independently so
+/// that a single malformed conversation does not abort the whole export.
+pub struct BulkEntry {
+    pub index: usize,
+    pub title: Option<String>,
+    pub conversation: Result<Conversation>,
+}
+
+/// Returns true if the document is an account export, i.e. an array of
+/// conversations rather than a single conversation.
+pub fn is_bulk_export(value: &Value) -> bool {
+    value.is_array()
+}
+
+/// Converts every conversation in an account export such as
+/// ChatGPT's `conversations.json`.
+pub fn parse_bulk_value(
+    value: Value,
+    source: ChatSource,
+    config: &MarkdownConfig,
+) -> Result<Vec<BulkEntry>> {
+    let Value::Array(items) = value else {
+        return Err(ParseError::NotAnExport(
+            "expected an array of conversations".to_string(),
+        ));
+    };
+
+    Ok(items
+        .into_iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let title = item
+                .get("title")
+                .or_else(|| item.get("name"))
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            BulkEntry {
+                index,
+                title,
+                conversation: parse_conversation_value(item, source, config),
+            }
+        })
+        .collect())
+}
+
+/// Reads the project names from a Claude export's `projects.json`,
+/// keyed by project uuid.
+pub fn parse_projects(json: &str) -> Result<HashMap<String, String>> {
+    let projects: Vec<ClaudeProject> = serde_json::from_str(json)?;
+    Ok(projects
+        .into_iter()
+        .map(|project| (project.uuid, project.name))
+        .collect())
+}
+
+/// Fills in project names for conversations that only reference
+/// their project by id.
+pub fn apply_project_names(entries: &mut [BulkEntry], projects: &HashMap<String, String>) {
+    for entry in entries {
+        if let Ok(conversation) = &mut entry.conversation {
+            if conversation.project_name.is_none() {
+                conversation.project_name = conversation
+                    .project_id
+                    .as_ref()
+                    .and_then(|id| projects.get(id))
+                    .cloned();
+            }
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use crate::config::ChatSource;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+/// A chat in a source-neutral form. Every platform model is converted
+/// into this structure, and all output is rendered from it.
+pub struct Conversation {
+    pub id: Option<String>,
+    pub title: String,
+    pub source: ChatSource,
+    pub project_id: Option<String>,
+    pub project_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub turns: Vec<Turn>,
+    pub branches: Vec<Branch>,
+    /// Messages the platform hides from the conversation, such as custom
+    /// instructions, kept only when hidden context is enabled
+    pub hidden_context: Vec<Turn>,
+    /// What the conversion could not make sense of, such as content of an
+    /// unknown type, for the caller to report
+    pub warnings: Vec<String>,
+}
+
+/// Turns that diverge from the exported path, such as a regenerated
+/// answer or an edited prompt. `forks_after` is the id of the turn the
+/// branch continues from, or `None` if it starts the conversation.
+pub struct Branch {
+    pub forks_after: Option<String>,
+    pub turns: Vec<Turn>,
+}
+
+/// A single message from one participant, in display order.
+pub struct Turn {
+    pub id: Option<String>,
+    pub role: Role,
+    pub created_at: Option<DateTime<Utc>>,
+    pub model: Option<String>,
+    pub blocks: Vec<Block>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Role {
+    User,
+    Assistant,
+    System,
+    Tool,
+    Other(String),
+}
+
+pub enum Block {
+    Text(String),
+    Thinking {
+        text: String,
+        summaries: Vec<String>,
+        started_at: Option<DateTime<Utc>>,
+    },
+    /// A document the assistant created or changed. `kind` is its MIME
+    /// type and `command` how it was changed, e.g. `create` or `update`.
+    /// `content` is the whole document as of this turn.
+    Artifact {
+        id: Option<String>,
+        title: Option<String>,
+        language: Option<String>,
+        kind: Option<String>,
+        command: Option<String>,
+        content: String,
+    },
+    /// Code the assistant ran, such as in ChatGPT's code interpreter,
+    /// with what it printed and the files it generated.
+    Execution {
+        language: Option<String>,
+        code: String,
+        stdout: Option<String>,
+        stderr: Option<String>,
+        files: Vec<String>,
+    },
+    /// An uploaded or generated image. `asset` is the platform's file id,
+    /// and `path` where the image was copied, relative to the output, if
+    /// the export included it. `prompt` is what generated the image.
+    Image {
+        asset: String,
+        width: Option<u32>,
+        height: Option<u32>,
+        size_bytes: Option<u64>,
+        prompt: Option<String>,
+        path: Option<String>,
+    },
+    Attachment {
+        name: String,
+        content: Option<String>,
+        size_bytes: Option<u64>,
+    },
+    Citation {
+        title: Option<String>,
+        url: Option<String>,
+        text: String,
+    },
+    ToolCall {
+        name: String,
+        input: Option<Value>,
+    },
+    /// Pages cited in the turn, which its text refers to as footnotes.
+    Sources(Vec<Source>),
+    /// Pages a browsing or search tool visited or found for the turn.
+    Browsing(Vec<Page>),
+}
+
+/// A cited page. `number` is the footnote the text refers to it by, and
+/// is unique within the conversation.
+pub struct Source {
+    pub number: usize,
+    pub title: Option<String>,
+    pub url: String,
+    pub domain: Option<String>,
+}
+
+/// A page found while browsing. Older ChatGPT exports only name the
+/// domain of pages that were not cited, so the URL may be missing.
+#[derive(PartialEq, Eq)]
+pub struct Page {
+    pub title: Option<String>,
+    pub url: Option<String>,
+    pub domain: Option<String>,
+}
+
+impl Role {
+    /// Lowercase name of the role, e.g. for machine-readable output.
+    pub fn name(&self) -> &str {
+        match self {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::System => "system",
+            Role::Tool => "tool",
+            Role::Other(other) => other,
+        }
+    }
+}
+
+impl Turn {
+    pub fn new(role: Role, created_at: Option<DateTime<Utc>>) -> Self {
+        Self {
+            id: None,
+            role,
+            created_at,
+            model: None,
+            blocks: Vec::new(),
+        }
+    }
+}
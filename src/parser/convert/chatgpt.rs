@@ -0,0 +1,1052 @@
+use super::{convert_tree, select_leaf, warn_unknown};
+use crate::config::{ChatSource, MarkdownConfig};
+use crate::parser::conversation::{Block, Conversation, Page, Role, Source, Turn};
+use crate::parser::error::Result;
+use crate::parser::model::{
+    ChatGPTCanvasCall, ChatGPTCanvasUpdate, ChatGPTChat, ChatGPTContentPart, ChatGPTMessage,
+    ChatGPTMetadata, ChatGPTNode, ImageAssetPointer,
+};
+use crate::parser::timestamp::parse_unix;
+use crate::parser::tree::MessageTree;
+use crate::parser::types::ChatGPTContentType;
+use once_cell::sync::Lazy;
+use regex::{Regex, RegexBuilder};
+use std::collections::VecDeque;
+
+static CITATION_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s).*?").unwrap());
+
+// Terminal colour codes, which tracebacks from the code interpreter carry
+static ANSI_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\x1b\[[0-9;]*[A-Za-z]").unwrap());
+
+// Group references in the replacement of a canvas update, written the
+// Python way as \1 or \g<1>
+static PYTHON_GROUP_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\\(?:(\d+)|g<(\w+)>)|\$").unwrap());
+
+// Search hits in the result of an older browsing display, such as
+// 【0†Eiffel Tower†en.wikipedia.org】
+static BROWSING_RESULT_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"【\d+†([^†】]+)†([^†】]+)】").unwrap());
+
+fn strip_citations<S: AsRef<str>>(s: S) -> String {
+    CITATION_RE.replace_all(s.as_ref(), "").into_owned()
+}
+
+/// Host of a URL, shown with a source when the platform names none.
+fn url_domain(url: &str) -> Option<String> {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let host = rest.split(['/', '?', '#']).next()?;
+    let host = host.strip_prefix("www.").unwrap_or(host);
+    (!host.is_empty()).then(|| host.to_string())
+}
+
+/// Numbers the pages cited in a message. Numbers run on across the
+/// conversation so footnote labels never clash, while a page cited again
+/// in the same message keeps its number.
+struct Footnotes<'a> {
+    count: &'a mut usize,
+    sources: Vec<Source>,
+}
+
+impl Footnotes<'_> {
+    fn cite(&mut self, title: Option<&str>, url: &str, domain: Option<&str>) -> String {
+        let number = match self.sources.iter().find(|source| source.url == url) {
+            Some(source) => source.number,
+            None => {
+                *self.count += 1;
+                self.sources.push(Source {
+                    number: *self.count,
+                    title: title.map(str::to_string),
+                    url: url.to_string(),
+                    domain: domain.map(str::to_string).or_else(|| url_domain(url)),
+                });
+                *self.count
+            }
+        };
+        format!("[^{}]", number)
+    }
+}
+
+/// Replaces the citation markers in a text part with footnote references.
+/// Markers that cite no page, such as hidden ones, are removed, and
+/// entities are replaced with their name. Older exports give character
+/// offsets into the first part instead of the markers.
+fn add_footnotes(
+    text: &str,
+    metadata: &ChatGPTMetadata,
+    first_part: bool,
+    footnotes: &mut Footnotes,
+) -> String {
+    if let Some(references) = metadata
+        .content_references
+        .as_ref()
+        .filter(|r| !r.is_empty())
+    {
+        let mut text = text.to_string();
+        for reference in references {
+            let Some(marker) = reference
+                .matched_text
+                .as_deref()
+                .filter(|marker| !marker.trim().is_empty() && text.contains(*marker))
+            else {
+                continue;
+            };
+
+            let pages: Vec<_> = match &reference.items {
+                Some(items) if !items.is_empty() => items
+                    .iter()
+                    .filter_map(|item| {
+                        let url = item.url.as_deref()?;
+                        Some((item.title.as_deref(), url, item.attribution.as_deref()))
+                    })
+                    .collect(),
+                _ => reference
+                    .url
+                    .as_deref()
+                    .map(|url| {
+                        (
+                            reference.title.as_deref(),
+                            url,
+                            reference.attribution.as_deref(),
+                        )
+                    })
+                    .into_iter()
+                    .collect(),
+            };
+            if pages.is_empty() {
+                let name = reference.name.as_deref().unwrap_or_default();
+                text = text.replacen(marker, name, 1);
+                continue;
+            }
+
+            // Footnotes follow the cited words directly
+            let references: String = pages
+                .into_iter()
+                .map(|(title, url, domain)| footnotes.cite(title, url, domain))
+                .collect();
+            if let Some(start) = text.find(marker) {
+                let end = start + marker.len();
+                text = format!("{}{}{}", text[..start].trim_end(), references, &text[end..]);
+            }
+        }
+        return text;
+    }
+
+    let citations = match &metadata.citations {
+        Some(citations) if first_part => citations,
+        _ => return text.to_string(),
+    };
+    let chars: Vec<char> = text.chars().collect();
+    let mut ordered: Vec<_> = citations.iter().collect();
+    ordered.sort_by_key(|citation| citation.start_ix);
+
+    let mut cited = String::with_capacity(text.len());
+    let mut position = 0;
+    for citation in ordered {
+        if citation.start_ix < position || citation.end_ix > chars.len() {
+            continue;
+        }
+        cited.extend(&chars[position..citation.start_ix]);
+        if let Some(page) = &citation.metadata {
+            if let Some(url) = page.url.as_deref() {
+                cited.push_str(&footnotes.cite(page.title.as_deref(), url, None));
+            }
+        }
+        position = citation.end_ix;
+    }
+    cited.extend(&chars[position..]);
+    cited
+}
+
+fn map_role(role: &str) -> Role {
+    match role {
+        "user" => Role::User,
+        "assistant" => Role::Assistant,
+        "system" => Role::System,
+        "tool" => Role::Tool,
+        other => Role::Other(other.to_string()),
+    }
+}
+
+fn text_parts(parts: &[ChatGPTContentPart]) -> impl Iterator<Item = &str> {
+    parts.iter().filter_map(|part| match part {
+        ChatGPTContentPart::Text(text) => Some(text.as_str()),
+        _ => None,
+    })
+}
+
+/// File id an asset pointer refers to, e.g. `file-AbC123` for
+/// `file-service://file-AbC123`.
+fn asset_id(pointer: &str) -> &str {
+    pointer.rsplit("://").next().unwrap_or(pointer)
+}
+
+fn image_block(image: &ImageAssetPointer) -> Block {
+    Block::Image {
+        asset: asset_id(&image.asset_pointer).to_string(),
+        width: image.width,
+        height: image.height,
+        size_bytes: image.size_bytes,
+        prompt: image
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.dalle.as_ref())
+            .and_then(|dalle| dalle.prompt.clone()),
+        path: None,
+    }
+}
+
+fn build_tree(chat: &ChatGPTChat) -> MessageTree {
+    let mut tree = MessageTree::new();
+
+    // Insert breadth-first from the root so that siblings keep
+    // the order of each node's `children` list
+    let mut queue: VecDeque<&ChatGPTNode> = chat
+        .mapping
+        .values()
+        .filter(|node| {
+            node.parent
+                .as_ref()
+                .is_none_or(|parent| !chat.mapping.contains_key(parent))
+        })
+        .collect();
+
+    while let Some(node) = queue.pop_front() {
+        tree.insert(node.id.clone(), node.parent.clone());
+        queue.extend(node.children.iter().filter_map(|id| chat.mapping.get(id)));
+    }
+
+    tree
+}
+
+/// Whether ChatGPT keeps a message out of the conversation: system
+/// scaffolding, custom instructions and memories.
+fn is_hidden(message: &ChatGPTMessage) -> bool {
+    message.metadata.is_visually_hidden_from_conversation
+        || matches!(
+            message.content.content_type.as_str(),
+            "user_editable_context" | "model_editable_context"
+        )
+}
+
+fn is_reasoning_message(message: &ChatGPTMessage) -> bool {
+    // Identifies ChatGPT reasoning/thinking messages by checking for:
+    // - Tool or system messages that have text content
+    // - Tool or system messages with initial text "Reasoning" and finished text "Reasoned..."
+    // - Tool or system messages with initial text "Thinking" and finished text "Thought..."
+    (message.author.role == "tool" || message.author.role == "system")
+        && (message.content.content_type == "text"
+            || (message.metadata.initial_text.as_deref() == Some("Reasoning")
+                && message
+                    .metadata
+                    .finished_text
+                    .as_deref()
+                    .is_some_and(|text| text.starts_with("Reasoned")))
+            || (message.metadata.initial_text.as_deref() == Some("Thinking")
+                && message
+                    .metadata
+                    .finished_text
+                    .as_deref()
+                    .is_some_and(|text| text.starts_with("Thought"))))
+}
+
+/// Converts the content of a message. With a footnote count, citations
+/// become footnotes and a list of the cited pages ends the blocks;
+/// without one they are stripped.
+fn content_blocks(message: &ChatGPTMessage, footnote_count: Option<&mut usize>) -> Vec<Block> {
+    let content = &message.content;
+    let mut blocks = Vec::new();
+    let mut footnotes = footnote_count.map(|count| Footnotes {
+        count,
+        sources: Vec::new(),
+    });
+
+    match ChatGPTContentType::from(content.content_type.as_str()) {
+        ChatGPTContentType::Text
+        | ChatGPTContentType::MultimodalText
+        | ChatGPTContentType::Tool
+        | ChatGPTContentType::System => {
+            let mut first_part = true;
+            for part in &content.parts {
+                match part {
+                    ChatGPTContentPart::Text(text) => {
+                        let text = match &mut footnotes {
+                            Some(footnotes) => {
+                                add_footnotes(text, &message.metadata, first_part, footnotes)
+                            }
+                            None => text.to_string(),
+                        };
+                        blocks.push(Block::Text(strip_citations(text).trim().to_string()));
+                        first_part = false;
+                    }
+                    ChatGPTContentPart::ImageAssetPointer(image) => {
+                        blocks.push(image_block(image));
+                    }
+                    ChatGPTContentPart::Other(_) => {}
+                }
+            }
+        }
+        ChatGPTContentType::Code => {
+            // The code interpreter often reports its language as unknown,
+            // but it only runs Python
+            if let Some(text) = &content.text {
+                let language = content
+                    .language
+                    .as_deref()
+                    .filter(|language| *language != "unknown")
+                    .unwrap_or("python");
+                blocks.push(Block::Execution {
+                    language: Some(language.to_string()),
+                    code: text.trim().to_string(),
+                    stdout: None,
+                    stderr: None,
+                    files: Vec::new(),
+                });
+            }
+        }
+        ChatGPTContentType::ExecutionOutput => {
+            // Attached to the code that produced it, see `attach_output`
+        }
+        ChatGPTContentType::TetherBrowsingDisplay => {
+            // Attached to the answer, see `browsed_pages`
+        }
+        ChatGPTContentType::TetherQuote => {
+            if let Some(quoted) = &content.text {
+                blocks.push(Block::Citation {
+                    title: content.title.clone(),
+                    url: content.url.clone(),
+                    text: strip_citations(quoted).trim().to_string(),
+                });
+            }
+        }
+        ChatGPTContentType::UserEditableContext => {
+            for text in [&content.user_profile, &content.user_instructions]
+                .into_iter()
+                .flatten()
+            {
+                blocks.push(Block::Text(text.trim().to_string()));
+            }
+        }
+        ChatGPTContentType::ModelEditableContext => {
+            if let Some(text) = &content.model_set_context {
+                blocks.push(Block::Text(text.trim().to_string()));
+            }
+        }
+        ChatGPTContentType::Unknown(_) => {
+            // Reported by `convert_path`, keeping any text
+            for text in text_parts(&content.parts) {
+                blocks.push(Block::Text(text.trim().to_string()));
+            }
+        }
+    }
+
+    if let Some(footnotes) = footnotes.filter(|footnotes| !footnotes.sources.is_empty()) {
+        blocks.push(Block::Sources(footnotes.sources));
+    }
+
+    blocks
+}
+
+/// Pages a browsing display or web search tool message visited or found,
+/// or `None` if the message is not one.
+fn browsed_pages(message: &ChatGPTMessage) -> Option<Vec<Page>> {
+    let metadata = &message.metadata;
+
+    if message.content.content_type == "tether_browsing_display" {
+        let cited = metadata
+            .cite_metadata
+            .iter()
+            .flat_map(|cite| cite.metadata_list.iter().flatten());
+        let result = message.content.result.as_deref().unwrap_or_default();
+
+        let pages = BROWSING_RESULT_RE
+            .captures_iter(result)
+            .map(|captures| {
+                let title = captures[1].trim().to_string();
+                let url = cited
+                    .clone()
+                    .find(|page| page.title.as_deref() == Some(title.as_str()))
+                    .and_then(|page| page.url.clone());
+                Page {
+                    title: Some(title),
+                    url,
+                    domain: Some(captures[2].trim().to_string()),
+                }
+            })
+            .collect();
+        return Some(pages);
+    }
+
+    let groups = metadata
+        .search_result_groups
+        .as_ref()
+        .filter(|groups| message.author.role == "tool" && !groups.is_empty())?;
+    let pages = groups
+        .iter()
+        .flat_map(|group| {
+            group.entries.iter().flatten().map(|entry| Page {
+                title: entry.title.clone(),
+                url: entry.url.clone(),
+                domain: entry.attribution.clone().or_else(|| group.domain.clone()),
+            })
+        })
+        .collect();
+    Some(pages)
+}
+
+/// Blocks waiting for the answer they led to: the reasoning, followed by
+/// the pages browsed on the way.
+fn take_pending(thinking: &mut Vec<Block>, pages: &mut Vec<Page>) -> Vec<Block> {
+    let mut blocks = std::mem::take(thinking);
+    if !pages.is_empty() {
+        blocks.push(Block::Browsing(std::mem::take(pages)));
+    }
+    blocks
+}
+
+/// Fills in the result of a code interpreter run on the code block it
+/// follows. Output that failed is reported as stderr, and generated
+/// images are referenced by their file id.
+fn attach_output(turns: &mut [Turn], message: &ChatGPTMessage) {
+    let execution = turns
+        .last_mut()
+        .and_then(|turn| turn.blocks.last_mut())
+        .filter(|block| matches!(block, Block::Execution { .. }));
+    let Some(Block::Execution {
+        stdout,
+        stderr,
+        files,
+        ..
+    }) = execution
+    else {
+        return;
+    };
+
+    let result = message.metadata.aggregate_result.as_ref();
+    let failed = result.is_some_and(|result| {
+        result.in_kernel_exception.is_some() || result.status.as_deref() == Some("failed")
+    });
+
+    let text = message.content.text.as_deref().unwrap_or_default();
+    let text = ANSI_RE.replace_all(text, "").trim().to_string();
+    if !text.is_empty() {
+        if failed {
+            *stderr = Some(text);
+        } else {
+            *stdout = Some(text);
+        }
+    }
+
+    files.extend(
+        result
+            .iter()
+            .flat_map(|result| &result.messages)
+            .filter(|output| output.message_type == "image")
+            .filter_map(|output| output.image_url.as_deref())
+            .map(|url| asset_id(url).to_string()),
+    );
+}
+
+/// A canvas document as it stands after the latest call on the path.
+struct CanvasDocument {
+    id: Option<String>,
+    name: Option<String>,
+    kind: Option<String>,
+    content: String,
+}
+
+/// Canvas documents seen so far on the path. Updates do not say which
+/// document they change, so they apply to the one created or updated
+/// last, which is kept at the end.
+#[derive(Default)]
+struct Canvas {
+    documents: Vec<CanvasDocument>,
+}
+
+/// Language of a canvas document from its type: prose documents are
+/// Markdown, and code documents are typed `code/<language>`.
+fn canvas_language(kind: Option<&str>) -> Option<String> {
+    match kind? {
+        "document" => Some("markdown".to_string()),
+        "code/react" => Some("jsx".to_string()),
+        kind => kind.strip_prefix("code/").map(str::to_string),
+    }
+}
+
+/// Converts a Python replacement string to the syntax of the `regex`
+/// crate, whose `$` would otherwise start a group reference.
+fn python_replacement(replacement: &str) -> String {
+    PYTHON_GROUP_RE
+        .replace_all(replacement, |captures: &regex::Captures| {
+            match captures.get(1).or_else(|| captures.get(2)) {
+                Some(group) => format!("${{{}}}", group.as_str()),
+                None => "$$".to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// Applies a regex update to a canvas document. Patterns use Python
+/// syntax, where `.*` rewrites the whole document. Returns `None` if the
+/// pattern does not compile.
+fn apply_canvas_update(content: &str, update: &ChatGPTCanvasUpdate) -> Option<String> {
+    let re = RegexBuilder::new(&update.pattern)
+        .dot_matches_new_line(true)
+        .build()
+        .ok()?;
+    let replacement = python_replacement(&update.replacement);
+    Some(if update.multiple {
+        re.replace_all(content, replacement.as_str()).into_owned()
+    } else {
+        re.replace(content, replacement.as_str()).into_owned()
+    })
+}
+
+impl Canvas {
+    /// Applies a create or update call, returning the document as it
+    /// stands afterwards. An update with no document on the path to
+    /// apply to is dropped, and one whose pattern does not compile is
+    /// skipped with a warning, as the document then differs from the
+    /// one the user saw.
+    fn apply(&mut self, call: ChatGPTCanvasCall, warnings: &mut Vec<String>) -> Option<Block> {
+        let command = if let Some(content) = call.content {
+            self.documents.push(CanvasDocument {
+                id: None,
+                name: call.name,
+                kind: call.kind,
+                content,
+            });
+            "create"
+        } else {
+            let document = self.documents.pop()?;
+            let mut content = document.content;
+            for update in &call.updates {
+                match apply_canvas_update(&content, update) {
+                    Some(updated) => content = updated,
+                    None => {
+                        let warning = format!(
+                            "Canvas update skipped, its pattern does not compile: {}",
+                            update.pattern
+                        );
+                        if !warnings.contains(&warning) {
+                            warnings.push(warning);
+                        }
+                    }
+                }
+            }
+            let rewrite = !call.updates.is_empty()
+                && call.updates.iter().all(|update| update.pattern == ".*");
+            self.documents.push(CanvasDocument {
+                content,
+                ..document
+            });
+            if rewrite {
+                "rewrite"
+            } else {
+                "update"
+            }
+        };
+
+        let document = self.documents.last()?;
+        Some(Block::Artifact {
+            id: document.id.clone(),
+            title: document.name.clone(),
+            language: canvas_language(document.kind.as_deref()),
+            kind: document.kind.clone(),
+            command: Some(command.to_string()),
+            content: document.content.clone(),
+        })
+    }
+
+    /// Records the id a tool response gives the latest document, which
+    /// is only known once it has been created. Returns the id if it is new.
+    fn identify(&mut self, message: &ChatGPTMessage) -> Option<String> {
+        let id = message.metadata.canvas.as_ref()?.textdoc_id.clone()?;
+        let document = self.documents.last_mut()?;
+        if document.id.is_some() {
+            return None;
+        }
+        document.id = Some(id.clone());
+        Some(id)
+    }
+}
+
+fn is_canvas_response(message: &ChatGPTMessage) -> bool {
+    message.author.role == "tool"
+        && message
+            .author
+            .name
+            .as_deref()
+            .is_some_and(|name| name.starts_with("canmore."))
+}
+
+/// Gives the document a canvas call created the id its response carries.
+fn identify_artifact(turns: &mut [Turn], id: String) {
+    let artifact = turns
+        .iter_mut()
+        .rev()
+        .flat_map(|turn| turn.blocks.iter_mut().rev())
+        .find_map(|block| match block {
+            Block::Artifact { id, .. } if id.is_none() => Some(id),
+            _ => None,
+        });
+    if let Some(artifact) = artifact {
+        *artifact = Some(id);
+    }
+}
+
+fn convert_path(
+    chat: &ChatGPTChat,
+    ids: &[String],
+    config: &MarkdownConfig,
+    footnote_count: &mut usize,
+    canvas: &mut Canvas,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<Turn>> {
+    let messages = ids
+        .iter()
+        .filter_map(|id| chat.mapping.get(id))
+        .filter_map(|node| node.message.as_ref());
+    let mut turns: Vec<Turn> = Vec::with_capacity(ids.len());
+
+    // Reasoning messages are collected here and attached to
+    // the assistant turn that follows them
+    let mut pending_thinking: Vec<Block> = Vec::new();
+    let mut thinking_started_at = None;
+
+    // Pages browsed or searched are attached the same way
+    let mut pending_pages: Vec<Page> = Vec::new();
+
+    for message in messages {
+        let content = &message.content;
+
+        if let Some(pages) = browsed_pages(message) {
+            if pending_thinking.is_empty() && pending_pages.is_empty() {
+                thinking_started_at = message.created_at.map(parse_unix).transpose()?;
+            }
+            for page in pages {
+                if !pending_pages.contains(&page) {
+                    pending_pages.push(page);
+                }
+            }
+            continue;
+        }
+
+        // The output of a code interpreter run belongs to its code
+        if content.content_type == "execution_output" {
+            attach_output(&mut turns, message);
+            continue;
+        }
+
+        // Canvas responses only confirm a call, but name the document
+        // it created
+        if is_canvas_response(message) {
+            if let Some(id) = canvas.identify(message) {
+                identify_artifact(&mut turns, id);
+            }
+            continue;
+        }
+
+        // Hidden messages are only shown in the hidden context. Tool
+        // results are attached above even if hidden, as they belong to
+        // an answer that is shown
+        if is_hidden(message) {
+            continue;
+        }
+
+        // Skip if there are no text parts or all text parts are empty,
+        // unless there are images
+        if content.text.as_deref().unwrap_or_default().is_empty()
+            && text_parts(&content.parts).all(|s| s.trim().is_empty())
+            && !content
+                .parts
+                .iter()
+                .any(|part| matches!(part, ChatGPTContentPart::ImageAssetPointer(_)))
+        {
+            continue;
+        }
+
+        let canvas_document = message
+            .canvas_call()
+            .and_then(|call| canvas.apply(call, warnings));
+
+        // Skip 'code' content, except for code interpreter runs and canvas
+        // documents, as other tools such as browsing are sent their
+        // queries as code
+        if content.content_type == "code"
+            && message.recipient.as_deref() != Some("python")
+            && canvas_document.is_none()
+        {
+            continue;
+        }
+
+        let created_at = message.created_at.map(parse_unix).transpose()?;
+
+        if is_reasoning_message(message) {
+            if pending_thinking.is_empty() && pending_pages.is_empty() {
+                thinking_started_at = created_at;
+            }
+            let text = text_parts(&content.parts)
+                .map(|text| strip_citations(text).trim().to_string())
+                .filter(|text| !text.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            pending_thinking.push(Block::Thinking {
+                text,
+                summaries: Vec::new(),
+                started_at: created_at,
+            });
+            continue;
+        }
+
+        let mut turn = Turn::new(map_role(&message.author.role), created_at);
+        turn.id = Some(message.id.clone());
+        turn.model = message.metadata.model_slug.clone();
+
+        if !pending_thinking.is_empty() || !pending_pages.is_empty() {
+            let pending = take_pending(&mut pending_thinking, &mut pending_pages);
+            if turn.role == Role::Assistant {
+                turn.blocks.extend(pending);
+            } else {
+                let mut thinking_turn = Turn::new(Role::Assistant, thinking_started_at);
+                thinking_turn.blocks.extend(pending);
+                turns.push(thinking_turn);
+            }
+        }
+
+        match canvas_document {
+            Some(document) => turn.blocks.push(document),
+            None => {
+                if let ChatGPTContentType::Unknown(content_type) =
+                    ChatGPTContentType::from(content.content_type.as_str())
+                {
+                    warn_unknown(warnings, &content_type);
+                }
+                let footnote_count = (!config.strip_citations).then_some(&mut *footnote_count);
+                turn.blocks.extend(content_blocks(message, footnote_count));
+            }
+        }
+        turns.push(turn);
+    }
+
+    // Reasoning at the very end of the chat has no answer to attach to
+    if !pending_thinking.is_empty() || !pending_pages.is_empty() {
+        let mut thinking_turn = Turn::new(Role::Assistant, thinking_started_at);
+        thinking_turn.blocks = take_pending(&mut pending_thinking, &mut pending_pages);
+        turns.push(thinking_turn);
+    }
+
+    Ok(turns)
+}
+
+/// The hidden messages on a path, each as a turn of its own.
+fn hidden_context(chat: &ChatGPTChat, ids: &[String]) -> Result<Vec<Turn>> {
+    let messages = ids
+        .iter()
+        .filter_map(|id| chat.mapping.get(id))
+        .filter_map(|node| node.message.as_ref())
+        .filter(|message| is_hidden(message));
+
+    let mut turns = Vec::new();
+    for message in messages {
+        let blocks: Vec<Block> = content_blocks(message, None)
+            .into_iter()
+            .filter(|block| !matches!(block, Block::Text(text) if text.is_empty()))
+            .collect();
+        if blocks.is_empty() {
+            continue;
+        }
+
+        let created_at = message.created_at.map(parse_unix).transpose()?;
+        let mut turn = Turn::new(map_role(&message.author.role), created_at);
+        turn.id = Some(message.id.clone());
+        turn.blocks = blocks;
+        turns.push(turn);
+    }
+
+    Ok(turns)
+}
+
+pub fn convert(chat: &ChatGPTChat, config: &MarkdownConfig) -> Result<Conversation> {
+    let tree = build_tree(chat);
+    let leaf = select_leaf(&tree, config.leaf.as_deref(), Some(&chat.current_node))?;
+    let mut footnote_count = 0;
+    let mut warnings = Vec::new();
+    let (turns, branches) = convert_tree(&tree, &leaf, |ids| {
+        // Canvas updates build on earlier versions, so a branch first
+        // replays the calls before it forks
+        let mut canvas = Canvas::default();
+        let mut before_fork = ids.first().map(|id| tree.path_to(id)).unwrap_or_default();
+        before_fork.pop();
+        for message in before_fork
+            .iter()
+            .filter_map(|id| chat.mapping.get(id))
+            .filter_map(|node| node.message.as_ref())
+        {
+            if let Some(call) = message.canvas_call() {
+                canvas.apply(call, &mut warnings);
+            } else if is_canvas_response(message) {
+                canvas.identify(message);
+            }
+        }
+
+        convert_path(
+            chat,
+            ids,
+            config,
+            &mut footnote_count,
+            &mut canvas,
+            &mut warnings,
+        )
+    })?;
+
+    let hidden_context = if config.hidden_context {
+        hidden_context(chat, &tree.path_to(&leaf))?
+    } else {
+        Vec::new()
+    };
+
+    // Chats copied from the browser may lack an id, but the root
+    // message of the mapping identifies them as well
+    let id = chat
+        .conversation_id
+        .clone()
+        .or_else(|| chat.id.clone())
+        .or_else(|| tree.root().cloned());
+
+    Ok(Conversation {
+        id,
+        title: chat.title.clone().unwrap_or_else(|| "Untitled".to_string()),
+        source: ChatSource::ChatGPT,
+        project_id: None,
+        project_name: None,
+        created_at: parse_unix(chat.created_at)?,
+        updated_at: parse_unix(chat.updated_at)?,
+        turns,
+        branches,
+        hidden_context,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    /// A chat whose nodes are `(id, parent, author, recipient, content, metadata)`,
+    /// under an empty root node. The author is a role, or `role:name`.
+    fn chat(current_node: &str, nodes: &[(&str, &str, &str, &str, Value, Value)]) -> ChatGPTChat {
+        let mut mapping = json!({
+            "root": {"id": "root", "message": null, "parent": null, "children": []}
+        });
+        for (id, parent, author, recipient, content, metadata) in nodes {
+            let (role, name) = match author.split_once(':') {
+                Some((role, name)) => (role, Some(name)),
+                None => (*author, None),
+            };
+            mapping[*parent]["children"]
+                .as_array_mut()
+                .unwrap()
+                .push(json!(id));
+            mapping[*id] = json!({
+                "id": id,
+                "parent": parent,
+                "children": [],
+                "message": {
+                    "id": id,
+                    "author": {"role": role, "name": name},
+                    "create_time": 1735725600.0,
+                    "content": content,
+                    "status": "finished_successfully",
+                    "recipient": recipient,
+                    "metadata": metadata,
+                },
+            });
+        }
+
+        serde_json::from_value(json!({
+            "conversation_id": "c1",
+            "title": "Chat",
+            "create_time": 1735725600.0,
+            "update_time": 1735725700.0,
+            "current_node": current_node,
+            "mapping": mapping,
+        }))
+        .unwrap()
+    }
+
+    fn text(text: &str) -> Value {
+        json!({"content_type": "text", "parts": [text]})
+    }
+
+    fn code(call: Value) -> Value {
+        json!({"content_type": "code", "text": call.to_string()})
+    }
+
+    fn texts(turns: &[Turn]) -> Vec<&str> {
+        turns
+            .iter()
+            .flat_map(|turn| &turn.blocks)
+            .filter_map(|block| match block {
+                Block::Text(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// `u1 -> {a1, a1b}`, with the platform pointing at `a1`.
+    fn regenerated() -> ChatGPTChat {
+        chat(
+            "a1",
+            &[
+                ("u1", "root", "user", "all", text("Hi"), json!({})),
+                ("a1", "u1", "assistant", "all", text("Hello"), json!({})),
+                ("a1b", "u1", "assistant", "all", text("Hey"), json!({})),
+            ],
+        )
+    }
+
+    #[test]
+    fn current_node_is_exported_with_the_rest_as_branches() {
+        let conversation = convert(&regenerated(), &MarkdownConfig::default()).unwrap();
+
+        assert_eq!(texts(&conversation.turns), ["Hi", "Hello"]);
+        assert_eq!(conversation.branches.len(), 1);
+        assert_eq!(conversation.branches[0].forks_after.as_deref(), Some("u1"));
+        assert_eq!(texts(&conversation.branches[0].turns), ["Hey"]);
+    }
+
+    #[test]
+    fn requested_leaf_overrides_the_current_node() {
+        let config = MarkdownConfig {
+            leaf: Some("a1b".to_string()),
+            ..MarkdownConfig::default()
+        };
+        let conversation = convert(&regenerated(), &config).unwrap();
+        assert_eq!(texts(&conversation.turns), ["Hi", "Hey"]);
+
+        let config = MarkdownConfig {
+            leaf: Some("missing".to_string()),
+            ..MarkdownConfig::default()
+        };
+        assert!(convert(&regenerated(), &config).is_err());
+    }
+
+    #[test]
+    fn python_replacements_use_regex_syntax() {
+        assert_eq!(python_replacement(r"\1 and \g<name>"), "${1} and ${name}");
+        assert_eq!(python_replacement("costs $5"), "costs $$5");
+    }
+
+    /// A poem created in canvas, then changed by an update call with
+    /// the given updates.
+    fn poem(updates: Value) -> ChatGPTChat {
+        chat(
+            "a2",
+            &[
+                ("u1", "root", "user", "all", text("Write a poem"), json!({})),
+                (
+                    "c1",
+                    "u1",
+                    "assistant",
+                    "canmore.create_textdoc",
+                    code(
+                        json!({"name": "Poem", "type": "document", "content": "Cats sleep all day."}),
+                    ),
+                    json!({}),
+                ),
+                (
+                    "t1",
+                    "c1",
+                    "tool:canmore.create_textdoc",
+                    "all",
+                    text("Created"),
+                    json!({"canvas": {"textdoc_id": "doc1", "version": 1}}),
+                ),
+                ("u2", "t1", "user", "all", text("Change it"), json!({})),
+                (
+                    "c2",
+                    "u2",
+                    "assistant",
+                    "canmore.update_textdoc",
+                    code(json!({ "updates": updates })),
+                    json!({}),
+                ),
+                ("a2", "c2", "assistant", "all", text("Done."), json!({})),
+            ],
+        )
+    }
+
+    fn artifacts(conversation: &Conversation) -> Vec<(Option<&str>, Option<&str>, &str)> {
+        conversation
+            .turns
+            .iter()
+            .flat_map(|turn| &turn.blocks)
+            .filter_map(|block| match block {
+                Block::Artifact {
+                    id,
+                    command,
+                    content,
+                    ..
+                } => Some((id.as_deref(), command.as_deref(), content.as_str())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn canvas_updates_are_replayed_on_the_document() {
+        let chat = poem(json!([{
+            "pattern": "sleep (all) day",
+            "multiple": false,
+            "replacement": r"sleep \1 night",
+        }]));
+        let conversation = convert(&chat, &MarkdownConfig::default()).unwrap();
+
+        assert_eq!(
+            artifacts(&conversation),
+            [
+                (Some("doc1"), Some("create"), "Cats sleep all day."),
+                (Some("doc1"), Some("update"), "Cats sleep all night."),
+            ]
+        );
+        assert!(conversation.warnings.is_empty());
+    }
+
+    #[test]
+    fn canvas_updates_that_do_not_compile_are_reported() {
+        let chat = poem(json!([
+            {"pattern": "(unclosed", "multiple": false, "replacement": "x"},
+            {"pattern": "day", "multiple": false, "replacement": "night"},
+        ]));
+        let conversation = convert(&chat, &MarkdownConfig::default()).unwrap();
+
+        assert_eq!(
+            artifacts(&conversation)[1],
+            (Some("doc1"), Some("update"), "Cats sleep all night.")
+        );
+        assert_eq!(
+            conversation.warnings,
+            ["Canvas update skipped, its pattern does not compile: (unclosed"]
+        );
+    }
+
+    #[test]
+    fn canvas_rewrites_replace_the_whole_document() {
+        let rewrite = poem(json!([
+            {"pattern": ".*", "multiple": false, "replacement": "Dogs bark."},
+        ]));
+        let conversation = convert(&rewrite, &MarkdownConfig::default()).unwrap();
+        assert_eq!(
+            artifacts(&conversation)[1],
+            (Some("doc1"), Some("rewrite"), "Dogs bark.")
+        );
+
+        let empty = poem(json!([]));
+        let conversation = convert(&empty, &MarkdownConfig::default()).unwrap();
+        assert_eq!(
+            artifacts(&conversation)[1],
+            (Some("doc1"), Some("update"), "Cats sleep all day.")
+        );
+    }
+}
@@ -0,0 +1,426 @@
+use super::{convert_tree, select_leaf, warn_unknown};
+use crate::config::{ChatSource, MarkdownConfig};
+use crate::parser::conversation::{Block, Conversation, Role, Turn};
+use crate::parser::error::Result;
+use crate::parser::model::{ClaudeArtifact, ClaudeChat, ClaudeMessage};
+use crate::parser::timestamp::parse_rfc3339;
+use crate::parser::tree::MessageTree;
+use crate::parser::types::ClaudeContentType;
+use std::collections::HashMap;
+
+fn map_role(sender: &str) -> Role {
+    match sender {
+        "human" => Role::User,
+        "assistant" => Role::Assistant,
+        other => Role::Other(other.to_string()),
+    }
+}
+
+fn build_tree(chat: &ClaudeChat) -> MessageTree {
+    let mut tree = MessageTree::new();
+    let mut previous: Option<&str> = None;
+
+    for message in &chat.chat_messages {
+        // Account exports may omit the parent, in which
+        // case the messages form a single linear path
+        let parent = message
+            .parent_message_uuid
+            .as_deref()
+            .or(previous)
+            .map(str::to_string);
+        tree.insert(message.uuid.clone(), parent);
+        previous = Some(&message.uuid);
+    }
+
+    tree
+}
+
+/// The latest version of an artifact, which update commands apply to.
+struct ArtifactVersion {
+    title: Option<String>,
+    language: Option<String>,
+    kind: Option<String>,
+    content: String,
+}
+
+/// Applies an artifact command to the versions seen so far on the path,
+/// returning the artifact as it stands afterwards. An update to an
+/// artifact that was never created on the path has nothing to apply to
+/// and is dropped; one whose `old_str` is not found leaves it unchanged.
+fn replay_artifact(
+    artifact: ClaudeArtifact,
+    versions: &mut HashMap<String, ArtifactVersion>,
+) -> Option<Block> {
+    let previous = artifact.id.as_ref().and_then(|id| versions.get(id));
+
+    let content = match artifact.command.as_deref() {
+        Some("update") => {
+            let previous = &previous?.content;
+            match (&artifact.old_str, &artifact.new_str) {
+                (Some(old), Some(new)) if previous.contains(old.as_str()) => {
+                    previous.replacen(old.as_str(), new, 1)
+                }
+                _ => previous.clone(),
+            }
+        }
+        _ => artifact.content.or(artifact.code)?,
+    };
+
+    // Updates usually only carry the id, so the rest is kept
+    let title = artifact
+        .title
+        .or_else(|| previous.and_then(|version| version.title.clone()));
+    let language = artifact
+        .language
+        .or_else(|| previous.and_then(|version| version.language.clone()));
+    let kind = artifact
+        .kind
+        .or_else(|| previous.and_then(|version| version.kind.clone()));
+
+    if let Some(id) = &artifact.id {
+        versions.insert(
+            id.clone(),
+            ArtifactVersion {
+                title: title.clone(),
+                language: language.clone(),
+                kind: kind.clone(),
+                content: content.clone(),
+            },
+        );
+    }
+
+    Some(Block::Artifact {
+        id: artifact.id,
+        title,
+        language,
+        kind,
+        command: artifact.command,
+        content,
+    })
+}
+
+fn convert_message(
+    message: &ClaudeMessage,
+    artifacts: &mut HashMap<String, ArtifactVersion>,
+    warnings: &mut Vec<String>,
+) -> Result<Option<Turn>> {
+    // Older account exports only carry the plain text of each
+    // message, so fall back to it when there is no content
+    let legacy_text = message.content.is_empty() && !message.text.trim().is_empty();
+
+    // Skip if content is empty
+    if message.content.is_empty() && !legacy_text {
+        return Ok(None);
+    }
+
+    let mut turn = Turn::new(
+        map_role(&message.sender),
+        Some(parse_rfc3339(&message.created_at)?),
+    );
+    turn.id = Some(message.uuid.clone());
+
+    if legacy_text {
+        turn.blocks
+            .push(Block::Text(message.text.trim().to_string()));
+    }
+
+    for content in &message.content {
+        match ClaudeContentType::from(content.content_type.as_str()) {
+            ClaudeContentType::Text => {
+                if let Some(text) = &content.text {
+                    turn.blocks.push(Block::Text(text.trim().to_string()));
+                }
+            }
+            ClaudeContentType::Thinking => {
+                if let Some(thinking) = &content.thinking {
+                    let started_at = match &content.start_timestamp {
+                        Some(timestamp) => Some(parse_rfc3339(timestamp)?),
+                        None => None,
+                    };
+                    let summaries = content
+                        .summaries
+                        .iter()
+                        .flatten()
+                        .map(|summary| summary.summary.trim().to_string())
+                        .collect();
+                    turn.blocks.push(Block::Thinking {
+                        text: thinking.trim().to_string(),
+                        summaries,
+                        started_at,
+                    });
+                }
+            }
+            ClaudeContentType::ToolUse => {
+                let name = content.name.clone().unwrap_or_default();
+
+                // Artifacts are content in their own right; other
+                // tools are only recorded as calls
+                if name == "artifacts" {
+                    if let Some(artifact) = content.artifact() {
+                        turn.blocks.extend(replay_artifact(artifact, artifacts));
+                    }
+                } else {
+                    turn.blocks.push(Block::ToolCall {
+                        name,
+                        input: content.input.clone(),
+                    });
+                }
+            }
+            ClaudeContentType::ToolResult => {
+                // Skip tool result
+            }
+            ClaudeContentType::Unknown(content_type) => {
+                warn_unknown(warnings, &content_type);
+                if let Some(text) = &content.text {
+                    turn.blocks.push(Block::Text(text.trim().to_string()));
+                }
+            }
+        }
+    }
+
+    for attachment in &message.attachments {
+        turn.blocks.push(Block::Attachment {
+            name: attachment.file_name.clone(),
+            content: attachment.extracted_content.clone(),
+            size_bytes: attachment.file_size,
+        });
+    }
+
+    Ok(Some(turn))
+}
+
+pub fn convert(chat: &ClaudeChat, config: &MarkdownConfig) -> Result<Conversation> {
+    let messages: HashMap<&str, &ClaudeMessage> = chat
+        .chat_messages
+        .iter()
+        .map(|message| (message.uuid.as_str(), message))
+        .collect();
+
+    let tree = build_tree(chat);
+    let leaf = select_leaf(
+        &tree,
+        config.leaf.as_deref(),
+        chat.current_leaf_message_uuid.as_deref(),
+    )?;
+    let mut warnings = Vec::new();
+    let (turns, branches) = convert_tree(&tree, &leaf, |ids| {
+        // Artifact updates build on earlier versions, so a branch first
+        // replays the messages before it forks
+        let mut artifacts = HashMap::new();
+        let mut before_fork = ids.first().map(|id| tree.path_to(id)).unwrap_or_default();
+        before_fork.pop();
+        for message in before_fork
+            .iter()
+            .filter_map(|id| messages.get(id.as_str()))
+        {
+            convert_message(message, &mut artifacts, &mut warnings)?;
+        }
+
+        let mut turns = Vec::with_capacity(ids.len());
+        for message in ids.iter().filter_map(|id| messages.get(id.as_str())) {
+            turns.extend(convert_message(message, &mut artifacts, &mut warnings)?);
+        }
+        Ok(turns)
+    })?;
+
+    let title = chat
+        .name
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .unwrap_or("Untitled")
+        .to_string();
+    let project = chat.project.as_ref();
+
+    Ok(Conversation {
+        id: chat.uuid.clone(),
+        title,
+        source: ChatSource::Claude,
+        project_id: chat
+            .project_uuid
+            .clone()
+            .or_else(|| project.and_then(|project| project.uuid.clone())),
+        project_name: project.and_then(|project| project.name.clone()),
+        created_at: parse_rfc3339(&chat.created_at)?,
+        updated_at: parse_rfc3339(&chat.updated_at)?,
+        turns,
+        branches,
+        hidden_context: Vec::new(),
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(uuid: &str, parent: &str, sender: &str, content: &str) -> String {
+        format!(
+            r#"{{"uuid": "{}", "parent_message_uuid": "{}", "sender": "{}",
+                "created_at": "2025-01-01T10:00:00Z", "content": {}}}"#,
+            uuid, parent, sender, content
+        )
+    }
+
+    fn text(text: &str) -> String {
+        format!(r#"[{{"type": "text", "text": "{}"}}]"#, text)
+    }
+
+    fn chat(current_leaf: Option<&str>, messages: &[String]) -> ClaudeChat {
+        let json = format!(
+            r#"{{"uuid": "c1", "name": "Chat", "created_at": "2025-01-01T10:00:00Z",
+                "updated_at": "2025-01-01T10:01:00Z", "current_leaf_message_uuid": {},
+                "chat_messages": [{}]}}"#,
+            serde_json::to_string(&current_leaf).unwrap(),
+            messages.join(",")
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    /// `u1 -> {a1, a1b}`, where `u1` points at a placeholder parent.
+    fn regenerated(current_leaf: Option<&str>) -> ClaudeChat {
+        chat(
+            current_leaf,
+            &[
+                message(
+                    "u1",
+                    "00000000-0000-4000-8000-000000000000",
+                    "human",
+                    &text("Hi"),
+                ),
+                message("a1", "u1", "assistant", &text("Hello")),
+                message("a1b", "u1", "assistant", &text("Hey")),
+            ],
+        )
+    }
+
+    fn texts(turns: &[Turn]) -> Vec<&str> {
+        turns
+            .iter()
+            .flat_map(|turn| &turn.blocks)
+            .filter_map(|block| match block {
+                Block::Text(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn artifact(input: &str) -> String {
+        format!(
+            r#"[{{"type": "tool_use", "name": "artifacts", "input": {}}}]"#,
+            input
+        )
+    }
+
+    /// A script created in `a1` and updated in `a2`, with `a2b` updating
+    /// it differently after the same prompt.
+    fn script_chat() -> ClaudeChat {
+        chat(
+            Some("a2"),
+            &[
+                message("u1", "root", "human", &text("Write it")),
+                message(
+                    "a1",
+                    "u1",
+                    "assistant",
+                    &artifact(
+                        r#"{"id": "script", "command": "create", "type": "application/vnd.ant.code",
+                            "language": "python", "title": "Script", "content": "print('a')"}"#,
+                    ),
+                ),
+                message("u2", "a1", "human", &text("Change it")),
+                message(
+                    "a2",
+                    "u2",
+                    "assistant",
+                    &artifact(
+                        r#"{"id": "script", "command": "update", "old_str": "'a'", "new_str": "'b'"}"#,
+                    ),
+                ),
+                message(
+                    "a2b",
+                    "u2",
+                    "assistant",
+                    &artifact(
+                        r#"{"id": "script", "command": "update", "old_str": "'a'", "new_str": "'c'"}"#,
+                    ),
+                ),
+            ],
+        )
+    }
+
+    fn artifact_content(turn: &Turn) -> Option<(&str, &str)> {
+        turn.blocks.iter().find_map(|block| match block {
+            Block::Artifact {
+                command, content, ..
+            } => Some((command.as_deref().unwrap_or_default(), content.as_str())),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn current_leaf_is_exported_with_the_rest_as_branches() {
+        let conversation = convert(&regenerated(Some("a1")), &MarkdownConfig::default()).unwrap();
+
+        assert_eq!(texts(&conversation.turns), ["Hi", "Hello"]);
+        assert_eq!(conversation.branches.len(), 1);
+        assert_eq!(conversation.branches[0].forks_after.as_deref(), Some("u1"));
+        assert_eq!(texts(&conversation.branches[0].turns), ["Hey"]);
+    }
+
+    #[test]
+    fn latest_leaf_is_exported_without_a_current_one() {
+        let conversation = convert(&regenerated(None), &MarkdownConfig::default()).unwrap();
+        assert_eq!(texts(&conversation.turns), ["Hi", "Hey"]);
+    }
+
+    #[test]
+    fn requested_leaf_overrides_the_current_one() {
+        let config = MarkdownConfig {
+            leaf: Some("a1b".to_string()),
+            ..MarkdownConfig::default()
+        };
+        let conversation = convert(&regenerated(Some("a1")), &config).unwrap();
+        assert_eq!(texts(&conversation.turns), ["Hi", "Hey"]);
+    }
+
+    #[test]
+    fn artifact_updates_are_replayed_on_the_previous_version() {
+        let conversation = convert(&script_chat(), &MarkdownConfig::default()).unwrap();
+
+        let contents: Vec<_> = conversation
+            .turns
+            .iter()
+            .filter_map(artifact_content)
+            .collect();
+        assert_eq!(
+            contents,
+            [("create", "print('a')"), ("update", "print('b')")]
+        );
+    }
+
+    #[test]
+    fn branches_replay_artifacts_from_the_version_they_fork_from() {
+        let conversation = convert(&script_chat(), &MarkdownConfig::default()).unwrap();
+
+        assert_eq!(conversation.branches.len(), 1);
+        let branch = &conversation.branches[0];
+        assert_eq!(branch.forks_after.as_deref(), Some("u2"));
+        assert_eq!(
+            artifact_content(&branch.turns[0]),
+            Some(("update", "print('c')"))
+        );
+    }
+
+    #[test]
+    fn requested_leaf_gets_its_own_artifact_version() {
+        let config = MarkdownConfig {
+            leaf: Some("a2b".to_string()),
+            ..MarkdownConfig::default()
+        };
+        let conversation = convert(&script_chat(), &config).unwrap();
+
+        let last = conversation.turns.last().unwrap();
+        assert_eq!(artifact_content(last), Some(("update", "print('c')")));
+    }
+}
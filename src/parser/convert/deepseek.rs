@@ -0,0 +1,100 @@
+use super::{convert_tree, select_leaf};
+use crate::config::{ChatSource, MarkdownConfig};
+use crate::parser::conversation::{Block, Conversation, Role, Turn};
+use crate::parser::error::Result;
+use crate::parser::model::{DeepSeekChat, DeepSeekMessage, DeepSeekResponse};
+use crate::parser::timestamp::parse_unix;
+use crate::parser::tree::MessageTree;
+use std::collections::HashMap;
+
+fn map_role(role: &str) -> Role {
+    match role {
+        "USER" => Role::User,
+        "ASSISTANT" => Role::Assistant,
+        other => Role::Other(other.to_string()),
+    }
+}
+
+fn build_tree(chat: &DeepSeekChat) -> MessageTree {
+    let mut tree = MessageTree::new();
+    for message in &chat.chat_messages {
+        tree.insert(
+            message.message_id.to_string(),
+            message.parent_id.map(|id| id.to_string()),
+        );
+    }
+    tree
+}
+
+fn convert_message(message: &DeepSeekMessage) -> Result<Option<Turn>> {
+    // If there is no text AND no thinking content, skip
+    if message.thinking_content.is_none() && message.content.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let mut turn = Turn::new(
+        map_role(&message.role),
+        Some(parse_unix(message.inserted_at)?),
+    );
+    turn.id = Some(message.message_id.to_string());
+    // Prompts are recorded with an empty model
+    turn.model = Some(message.model.clone()).filter(|model| !model.is_empty());
+
+    if message.thinking_enabled {
+        if let Some(thinking) = &message.thinking_content {
+            turn.blocks.push(Block::Thinking {
+                text: thinking.trim().to_string(),
+                summaries: Vec::new(),
+                started_at: None,
+            });
+        }
+    }
+
+    turn.blocks
+        .push(Block::Text(message.content.trim().to_string()));
+
+    for file in &message.files {
+        turn.blocks.push(Block::Attachment {
+            name: file.file_name.clone(),
+            content: None,
+            size_bytes: u64::try_from(file.file_size).ok(),
+        });
+    }
+
+    Ok(Some(turn))
+}
+
+pub fn convert(response: &DeepSeekResponse, config: &MarkdownConfig) -> Result<Conversation> {
+    let chat = &response.data.biz_data;
+    let messages: HashMap<String, &DeepSeekMessage> = chat
+        .chat_messages
+        .iter()
+        .map(|message| (message.message_id.to_string(), message))
+        .collect();
+
+    // Walk back from the current message to get the conversation the user sees
+    let tree = build_tree(chat);
+    let current = chat.chat_session.current_message_id.to_string();
+    let leaf = select_leaf(&tree, config.leaf.as_deref(), Some(&current))?;
+    let (turns, branches) = convert_tree(&tree, &leaf, |ids| {
+        let mut turns = Vec::with_capacity(ids.len());
+        for message in ids.iter().filter_map(|id| messages.get(id)) {
+            turns.extend(convert_message(message)?);
+        }
+        Ok(turns)
+    })?;
+
+    Ok(Conversation {
+        id: Some(chat.chat_session.id.clone()),
+        title: chat.chat_session.title.clone(),
+        source: ChatSource::DeepSeek,
+        project_id: None,
+        project_name: None,
+        created_at: parse_unix(chat.chat_session.inserted_at)?,
+        updated_at: parse_unix(chat.chat_session.updated_at)?,
+        turns,
+        branches,
+        hidden_context: Vec::new(),
+        warnings: Vec::new(),
+    })
+}
@@ -0,0 +1,123 @@
+mod chatgpt;
+mod claude;
+mod deepseek;
+
+pub use chatgpt::convert as convert_chatgpt;
+pub use claude::convert as convert_claude;
+pub use deepseek::convert as convert_deepseek;
+
+use crate::config::{ChatSource, MarkdownConfig};
+use crate::parser::conversation::{Branch, Conversation, Turn};
+use crate::parser::detect::detect_source;
+use crate::parser::error::{ParseError, Result};
+use crate::parser::tree::MessageTree;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// Parses raw JSON from the given platform into a conversation.
+pub fn parse_conversation(
+    json: &str,
+    source: ChatSource,
+    config: &MarkdownConfig,
+) -> Result<Conversation> {
+    parse_conversation_value(serde_json::from_str(json)?, source, config)
+}
+
+/// Converts an already parsed JSON document into a conversation,
+/// detecting the platform first when the source is `Auto`.
+pub fn parse_conversation_value(
+    value: Value,
+    source: ChatSource,
+    config: &MarkdownConfig,
+) -> Result<Conversation> {
+    match source {
+        ChatSource::Auto => {
+            let detected = detect_source(&value)?;
+            parse_conversation_value(value, detected, config)
+        }
+        ChatSource::Claude => convert_claude(&deserialize(&value, source)?, config),
+        ChatSource::ChatGPT => convert_chatgpt(&deserialize(&value, source)?, config),
+        ChatSource::DeepSeek => convert_deepseek(&deserialize(&value, source)?, config),
+    }
+}
+
+/// Notes content of a type the converter does not know, once per
+/// conversation. Its text is still kept where there is any.
+fn warn_unknown(warnings: &mut Vec<String>, content_type: &str) {
+    let warning = format!("Unknown content type: {}", content_type);
+    if !warnings.contains(&warning) {
+        warnings.push(warning);
+    }
+}
+
+/// Picks the leaf whose path is exported: the one requested in the config,
+/// else the one the platform marks as current, else the latest one.
+fn select_leaf(
+    tree: &MessageTree,
+    requested: Option<&str>,
+    current: Option<&str>,
+) -> Result<String> {
+    if let Some(requested) = requested {
+        if !tree.contains(requested) {
+            return Err(ParseError::UnknownLeaf(requested.to_string()));
+        }
+        return Ok(requested.to_string());
+    }
+
+    current
+        .filter(|current| tree.contains(current))
+        .map(str::to_string)
+        .or_else(|| tree.latest_leaf())
+        .ok_or_else(|| ParseError::UnknownLeaf("chat has no messages".to_string()))
+}
+
+/// Converts the selected path and every branch that diverges from it.
+/// `convert_path` turns a run of message ids into turns.
+fn convert_tree(
+    tree: &MessageTree,
+    leaf: &str,
+    mut convert_path: impl FnMut(&[String]) -> Result<Vec<Turn>>,
+) -> Result<(Vec<Turn>, Vec<Branch>)> {
+    let main = tree.path_to(leaf);
+    let turns = convert_path(&main)?;
+
+    let mut turn_ids: HashSet<String> = turns.iter().filter_map(|turn| turn.id.clone()).collect();
+    let mut branches = Vec::new();
+
+    for branch in tree.branches(&main) {
+        let branch_turns = convert_path(&branch.ids)?;
+        if branch_turns.is_empty() {
+            continue;
+        }
+
+        // Skipped messages have no turn, so attach the branch
+        // to the closest ancestor that was rendered
+        let forks_after = branch.forks_after.and_then(|fork| {
+            tree.path_to(&fork)
+                .into_iter()
+                .rev()
+                .find(|id| turn_ids.contains(id))
+        });
+
+        turn_ids.extend(branch_turns.iter().filter_map(|turn| turn.id.clone()));
+        branches.push(Branch {
+            forks_after,
+            turns: branch_turns,
+        });
+    }
+
+    Ok((turns, branches))
+}
+
+fn deserialize<'a, T: Deserialize<'a>>(value: &'a Value, source: ChatSource) -> Result<T> {
+    T::deserialize(value).map_err(|err| match detect_source(value) {
+        // A missing field usually means the wrong source was selected
+        Ok(detected) if detected != source => ParseError::SourceMismatch {
+            expected: source.platform_name(),
+            detected: detected.platform_name(),
+            source: err,
+        },
+        _ => ParseError::Json(err),
+    })
+}
@@ -0,0 +1,228 @@
+use crate::config::{MarkdownConfig, OutputFormat};
+use crate::parser::conversation::{Block, Conversation, Role, Turn};
+use crate::parser::error::Result;
+use crate::parser::markdown::fence_for;
+use serde::Serialize;
+
+/// A message as it appears in a training example.
+struct Message {
+    role: Role,
+    content: String,
+}
+
+#[derive(Serialize)]
+struct OpenAiMessage<'a> {
+    role: &'static str,
+    content: &'a str,
+}
+
+#[derive(Serialize)]
+struct OpenAiExample<'a> {
+    messages: Vec<OpenAiMessage<'a>>,
+}
+
+#[derive(Serialize)]
+struct ShareGptMessage<'a> {
+    from: &'static str,
+    value: &'a str,
+}
+
+#[derive(Serialize)]
+struct ShareGptExample<'a> {
+    id: Option<&'a str>,
+    conversations: Vec<ShareGptMessage<'a>>,
+}
+
+fn push_paragraph(content: &mut String, paragraph: &str) {
+    if !content.is_empty() {
+        content.push_str("\n\n");
+    }
+    content.push_str(paragraph);
+}
+
+fn fenced(language: Option<&str>, content: &str) -> String {
+    let fence = fence_for(content);
+    format!(
+        "{}{}\n{}\n{}",
+        fence,
+        language.unwrap_or_default(),
+        content,
+        fence
+    )
+}
+
+/// Flattens a turn into plain text. Reasoning is wrapped in `<think>` tags
+/// ahead of the answer, and tool calls are left out.
+fn turn_content(turn: &Turn, config: &MarkdownConfig) -> String {
+    let mut thinking = String::new();
+    let mut content = String::new();
+
+    for block in &turn.blocks {
+        match block {
+            Block::Text(text) => push_paragraph(&mut content, text),
+            Block::Thinking { text, .. } if config.reasoning => {
+                push_paragraph(&mut thinking, text);
+            }
+            Block::Thinking { .. } => {}
+            Block::Artifact {
+                language,
+                content: artifact,
+                ..
+            } => {
+                push_paragraph(&mut content, &fenced(language.as_deref(), artifact));
+            }
+            Block::Execution {
+                language,
+                code,
+                stdout,
+                stderr,
+                ..
+            } if config.code_execution => {
+                push_paragraph(&mut content, &fenced(language.as_deref(), code));
+                for output in [stdout, stderr].into_iter().flatten() {
+                    push_paragraph(&mut content, &fenced(None, output));
+                }
+            }
+            Block::Execution { .. } => {}
+            Block::Attachment {
+                name,
+                content: Some(attachment),
+                ..
+            } if config.attachments => {
+                let attachment = format!("Attachment: {}\n{}", name, fenced(None, attachment));
+                push_paragraph(&mut content, &attachment);
+            }
+            Block::Attachment { .. } => {}
+            Block::Citation { text, .. } => {
+                let quote = text
+                    .lines()
+                    .map(|line| format!("> {}", line))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                push_paragraph(&mut content, &quote);
+            }
+            Block::ToolCall { .. }
+            | Block::Sources(_)
+            | Block::Browsing(_)
+            | Block::Image { .. } => {}
+        }
+    }
+
+    if thinking.is_empty() {
+        content
+    } else {
+        format!("<think>\n{}\n</think>\n\n{}", thinking, content)
+    }
+}
+
+/// The exported path as training messages. Empty turns are dropped and
+/// consecutive turns from the same role are merged, so the roles alternate
+/// as most trainers expect. A final prompt that was never answered is
+/// dropped, since there is nothing to learn from it.
+///
+/// Tool output is folded into the assistant's message as a fenced block:
+/// the exports do not keep the calls that produced it, and both formats
+/// reject tool messages without them.
+fn messages(conversation: &Conversation, config: &MarkdownConfig) -> Vec<Message> {
+    let mut messages: Vec<Message> = Vec::new();
+
+    for turn in &conversation.turns {
+        let role = match &turn.role {
+            Role::User | Role::Assistant => turn.role.clone(),
+            _ if !config.system_messages => continue,
+            Role::Tool => Role::Assistant,
+            _ => Role::System,
+        };
+
+        let content = turn_content(turn, config);
+        if content.trim().is_empty() {
+            continue;
+        }
+        let content = match turn.role {
+            Role::Tool => format!("Tool output:\n{}", fenced(None, &content)),
+            _ => content,
+        };
+
+        match messages.last_mut() {
+            Some(last) if last.role == role => push_paragraph(&mut last.content, &content),
+            _ => messages.push(Message { role, content }),
+        }
+    }
+
+    while messages
+        .last()
+        .is_some_and(|message| message.role != Role::Assistant)
+    {
+        messages.pop();
+    }
+
+    messages
+}
+
+fn openai_role(role: &Role) -> &'static str {
+    match role {
+        Role::User => "user",
+        Role::Assistant => "assistant",
+        _ => "system",
+    }
+}
+
+fn sharegpt_role(role: &Role) -> &'static str {
+    match role {
+        Role::User => "human",
+        Role::Assistant => "gpt",
+        _ => "system",
+    }
+}
+
+/// Renders conversations as a fine-tuning dataset: one OpenAI chat example
+/// per line for `openai`, or a ShareGPT array for `sharegpt`. Only the
+/// exported path of each conversation is used, and conversations without
+/// any messages are skipped.
+pub fn render_dataset(conversations: &[&Conversation], config: &MarkdownConfig) -> Result<String> {
+    let examples: Vec<_> = conversations
+        .iter()
+        .map(|conversation| (conversation, messages(conversation, config)))
+        .filter(|(_, messages)| !messages.is_empty())
+        .collect();
+
+    match config.format {
+        OutputFormat::ShareGpt => {
+            let examples: Vec<_> = examples
+                .iter()
+                .map(|(conversation, messages)| ShareGptExample {
+                    id: conversation.id.as_deref(),
+                    conversations: messages
+                        .iter()
+                        .map(|message| ShareGptMessage {
+                            from: sharegpt_role(&message.role),
+                            value: &message.content,
+                        })
+                        .collect(),
+                })
+                .collect();
+
+            let mut json = serde_json::to_string_pretty(&examples)?;
+            json.push('\n');
+            Ok(json)
+        }
+        // Any other format is written as OpenAI chat JSONL
+        _ => {
+            let mut jsonl = String::new();
+            for (_, messages) in &examples {
+                let example = OpenAiExample {
+                    messages: messages
+                        .iter()
+                        .map(|message| OpenAiMessage {
+                            role: openai_role(&message.role),
+                            content: &message.content,
+                        })
+                        .collect(),
+                };
+                jsonl.push_str(&serde_json::to_string(&example)?);
+                jsonl.push('\n');
+            }
+            Ok(jsonl)
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use crate::config::ChatSource;
+use crate::parser::error::{ParseError, Result};
+use serde_json::Value;
+
+/// Identifies the platform a chat was exported from by the shape of its JSON.
+pub fn detect_source(value: &Value) -> Result<ChatSource> {
+    // Claude: a `chat_messages` array alongside the conversation `uuid`
+    if value.get("chat_messages").is_some_and(Value::is_array) && value.get("uuid").is_some() {
+        return Ok(ChatSource::Claude);
+    }
+
+    // ChatGPT: a `mapping` of message nodes and the `current_node` id
+    if value.get("mapping").is_some_and(Value::is_object) && value.get("current_node").is_some() {
+        return Ok(ChatSource::ChatGPT);
+    }
+
+    // DeepSeek: the API response wraps the session in `data.biz_data`
+    if value.pointer("/data/biz_data/chat_session").is_some() {
+        return Ok(ChatSource::DeepSeek);
+    }
+
+    Err(ParseError::UnknownSource(
+        "checked for Claude (`chat_messages` and `uuid`), \
+         ChatGPT (`mapping` and `current_node`) and \
+         DeepSeek (`data.biz_data.chat_session`), but none matched"
+            .to_string(),
+    ))
+}
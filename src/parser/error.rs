@@ -14,6 +14,20 @@ pub enum ParseError {
     Format(#[from] std::fmt::Error),
     #[error("JSON error: {0}")]
     Json(#[from] JsonError),
+    #[error("Unknown leaf message: {0}")]
+    UnknownLeaf(String),
+    #[error("Not an account export: {0}")]
+    NotAnExport(String),
+    #[error("Could not detect chat source: {0}")]
+    UnknownSource(String),
+    #[error("Cannot update {0}")]
+    Update(String),
+    #[error("Input does not match chat source {expected}, it looks like {detected}: {source}")]
+    SourceMismatch {
+        expected: &'static str,
+        detected: &'static str,
+        source: JsonError,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, ParseError>;
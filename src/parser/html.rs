@@ -0,0 +1,549 @@
+use crate::parser::artifact::artifact_label;
+use crate::parser::conversation::{Block, Page, Role};
+use crate::parser::error::Result;
+use crate::parser::render::{image_placeholder, ConversationView, TurnView};
+use once_cell::sync::Lazy;
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd, TextMergeStream};
+use regex::Regex;
+use std::fmt::Write;
+use std::path::Path;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::html::highlighted_html_for_string;
+use syntect::parsing::SyntaxSet;
+
+static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
+static THEME: Lazy<Theme> =
+    Lazy::new(|| ThemeSet::load_defaults().themes["InspiredGitHub"].clone());
+
+// References to the sources listed at the end of a message
+static FOOTNOTE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[\^(\d+)\]").unwrap());
+
+const STYLE: &str = r#"
+body { margin: 0; background: #f6f7f9; color: #1f2328; font: 16px/1.6 -apple-system, BlinkMacSystemFont, "Segoe UI", Helvetica, Arial, sans-serif; }
+main { max-width: 860px; margin: 0 auto; padding: 2rem 1rem 4rem; }
+h1 { margin: 0 0 .5rem; font-size: 1.8rem; }
+.meta { display: grid; grid-template-columns: max-content 1fr; gap: .1rem 1rem; margin: 0 0 2rem; color: #59636e; font-size: .9rem; }
+.meta dt { font-weight: 600; }
+.meta dd { margin: 0; }
+.turn { background: #fff; border: 1px solid #d1d9e0; border-radius: 8px; padding: 1rem 1.25rem; margin: 0 0 1rem; }
+.turn.user { background: #eef4ff; border-color: #c5d7f5; }
+.turn-header { display: flex; gap: .5rem; align-items: baseline; margin-bottom: .5rem; }
+.turn-header .sender { font-weight: 600; }
+.turn-header time { color: #59636e; font-size: .85rem; }
+details { border: 1px solid #d1d9e0; border-radius: 6px; padding: .25rem .75rem; margin: .75rem 0; background: #fafbfc; }
+details > summary { cursor: pointer; font-weight: 600; color: #59636e; }
+details.thinking { border-style: dashed; }
+details.thinking .body { color: #3d444d; font-size: .95rem; }
+pre { overflow-x: auto; padding: .75rem; border-radius: 6px; border: 1px solid #e1e4e8; font-size: .85rem; line-height: 1.45; }
+code { font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; }
+:not(pre) > code { background: #eff1f3; padding: .1em .3em; border-radius: 4px; font-size: .9em; }
+img { max-width: 100%; height: auto; border-radius: 6px; }
+.image-placeholder { color: #59636e; font-style: italic; }
+.artifact-title, .attachment-title, .block-title { font-weight: 600; margin-top: .75rem; }
+figure.citation { margin: .75rem 0; }
+blockquote { margin: 0; padding: 0 1rem; border-left: 4px solid #d1d9e0; color: #3d444d; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #d1d9e0; padding: .25rem .5rem; }
+.branches { margin-top: 3rem; }
+.hidden-context { margin-bottom: 2rem; }
+.hidden-context .turn { border-style: dashed; }
+.fork, .model-switch, .hidden-note { color: #59636e; font-style: italic; }
+.turn-header .model { color: #59636e; font-size: .85rem; }
+"#;
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Whether a link or image URL is safe to follow from the page: web and
+/// mail links, or ones relative to it. Browsers skip whitespace and
+/// control characters in the scheme, so they are ignored here too.
+fn is_safe_url(url: &str) -> bool {
+    let url: String = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_control())
+        .collect();
+    match url.find([':', '/', '?', '#']) {
+        Some(end) if url[end..].starts_with(':') => {
+            matches!(
+                url[..end].to_ascii_lowercase().as_str(),
+                "http" | "https" | "mailto"
+            )
+        }
+        _ => true,
+    }
+}
+
+/// An `<a>` to the URL around the already escaped label, or just the
+/// label if the URL is not safe to link.
+fn link(url: &str, label: &str) -> String {
+    if is_safe_url(url) {
+        format!("<a href=\"{}\">{}</a>", escape(url), label)
+    } else {
+        label.to_string()
+    }
+}
+
+/// Highlights code for the given language token or file extension,
+/// falling back to plain text when the language is not recognised.
+fn highlight(code: &str, language: Option<&str>) -> String {
+    let syntax = language
+        .and_then(|language| SYNTAX_SET.find_syntax_by_token(language))
+        .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
+
+    highlighted_html_for_string(code, &SYNTAX_SET, syntax, &THEME)
+        .unwrap_or_else(|_| format!("<pre><code>{}</code></pre>\n", escape(code)))
+}
+
+/// Converts message Markdown to HTML. Raw HTML in the message is shown as
+/// text rather than interpreted, fenced code blocks are highlighted, and
+/// links and images with unsafe URLs are reduced to their text.
+fn markdown_to_html(text: &str) -> String {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES;
+
+    let mut events = Vec::new();
+    let mut code_block: Option<(Option<String>, String)> = None;
+    // Whether each open link or image is kept, as images can be linked
+    let mut kept_links: Vec<bool> = Vec::new();
+
+    for event in TextMergeStream::new(Parser::new_ext(text, options)) {
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => info
+                        .split_whitespace()
+                        .next()
+                        .map(|language| language.to_string()),
+                    CodeBlockKind::Indented => None,
+                };
+                code_block = Some((language, String::new()));
+            }
+            Event::Text(text) if code_block.is_some() => {
+                if let Some((_, code)) = &mut code_block {
+                    code.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some((language, code)) = code_block.take() {
+                    events.push(Event::Html(highlight(&code, language.as_deref()).into()));
+                }
+            }
+            Event::Text(text) => {
+                // Sources are listed apart from the text, so the footnote
+                // references are linked to them here
+                let mut last = 0;
+                for captures in FOOTNOTE_RE.captures_iter(&text) {
+                    let Some(reference) = captures.get(0) else {
+                        continue;
+                    };
+                    events.push(Event::Text(
+                        text[last..reference.start()].to_string().into(),
+                    ));
+                    let link = format!("<sup><a href=\"#source-{0}\">{0}</a></sup>", &captures[1]);
+                    events.push(Event::InlineHtml(link.into()));
+                    last = reference.end();
+                }
+                events.push(Event::Text(text[last..].to_string().into()));
+            }
+            Event::Start(Tag::Link { ref dest_url, .. })
+            | Event::Start(Tag::Image { ref dest_url, .. }) => {
+                let kept = is_safe_url(dest_url);
+                kept_links.push(kept);
+                if kept {
+                    events.push(event);
+                }
+            }
+            Event::End(TagEnd::Link) | Event::End(TagEnd::Image) => {
+                if kept_links.pop().unwrap_or(true) {
+                    events.push(event);
+                }
+            }
+            Event::Html(html) | Event::InlineHtml(html) => events.push(Event::Text(html)),
+            event => events.push(event),
+        }
+    }
+
+    let mut html = String::with_capacity(text.len() * 2);
+    pulldown_cmark::html::push_html(&mut html, events.into_iter());
+    html
+}
+
+/// Writes a browsed page as a list item, linked when its URL is known.
+fn write_page(html: &mut String, page: &Page) -> Result<()> {
+    let label = page
+        .title
+        .as_deref()
+        .or(page.url.as_deref())
+        .or(page.domain.as_deref())
+        .unwrap_or_default();
+    match &page.url {
+        Some(url) => write!(html, "<li>{}", link(url, &escape(label)))?,
+        None => write!(html, "<li>{}", escape(label))?,
+    }
+    match &page.domain {
+        Some(domain) if page.title.is_some() || page.url.is_some() => {
+            writeln!(html, " ({})</li>", escape(domain))?
+        }
+        _ => writeln!(html, "</li>")?,
+    }
+    Ok(())
+}
+
+fn write_block(html: &mut String, block: &Block, collapse_browsing: bool) -> Result<()> {
+    match block {
+        Block::Text(text) => {
+            html.push_str(&markdown_to_html(text));
+        }
+        Block::Thinking {
+            text, summaries, ..
+        } => {
+            html.push_str(&markdown_to_html(text));
+            // Show any provided summaries
+            if !summaries.is_empty() {
+                writeln!(html, "<p><strong>Thinking Summaries</strong></p>\n<ol>")?;
+                for summary in summaries {
+                    writeln!(html, "<li>{}</li>", escape(summary))?;
+                }
+                writeln!(html, "</ol>")?;
+            }
+        }
+        Block::Artifact {
+            id,
+            title,
+            language,
+            command,
+            content,
+            ..
+        } => {
+            if let Some(label) = artifact_label(id.as_deref(), title.as_deref(), command.as_deref())
+            {
+                writeln!(
+                    html,
+                    "<div class=\"artifact-title\">Artifact: {}</div>",
+                    escape(&label)
+                )?;
+            }
+            html.push_str(&highlight(content, language.as_deref()));
+        }
+        Block::Execution {
+            language,
+            code,
+            stdout,
+            stderr,
+            files,
+        } => {
+            html.push_str(&highlight(code, language.as_deref()));
+            if let Some(stdout) = stdout {
+                writeln!(html, "<div class=\"block-title\">Output</div>")?;
+                html.push_str(&highlight(stdout, None));
+            }
+            if let Some(stderr) = stderr {
+                writeln!(html, "<div class=\"block-title\">Error</div>")?;
+                html.push_str(&highlight(stderr, None));
+            }
+            if !files.is_empty() {
+                writeln!(
+                    html,
+                    "<div class=\"block-title\">Generated Files</div>\n<ul>"
+                )?;
+                for file in files {
+                    writeln!(html, "<li><code>{}</code></li>", escape(file))?;
+                }
+                writeln!(html, "</ul>")?;
+            }
+        }
+        Block::Image {
+            asset,
+            width,
+            height,
+            size_bytes,
+            prompt,
+            path,
+        } => {
+            match path {
+                Some(path) if is_safe_url(path) || path.starts_with("data:image/") => {
+                    writeln!(html, "<p><img src=\"{}\" alt=\"Image\"></p>", escape(path))?
+                }
+                _ => writeln!(
+                    html,
+                    "<p class=\"image-placeholder\">{}</p>",
+                    escape(&image_placeholder(asset, *width, *height, *size_bytes))
+                )?,
+            }
+            if let Some(prompt) = prompt {
+                writeln!(
+                    html,
+                    "<p class=\"image-placeholder\">Prompt: {}</p>",
+                    escape(prompt.trim())
+                )?;
+            }
+        }
+        Block::Attachment {
+            name,
+            content,
+            size_bytes,
+        } => {
+            let size = size_bytes
+                .map(|size| format!(" ({} bytes)", size))
+                .unwrap_or_default();
+            match content {
+                Some(content) => {
+                    let extension = Path::new(name).extension().and_then(|ext| ext.to_str());
+                    writeln!(
+                        html,
+                        "<details class=\"attachment\">\n<summary>Attachment: {}{}</summary>",
+                        escape(name),
+                        size
+                    )?;
+                    html.push_str(&highlight(content, extension));
+                    writeln!(html, "</details>")?;
+                }
+                None => {
+                    writeln!(
+                        html,
+                        "<div class=\"attachment-title\">Attachment: {}{}</div>",
+                        escape(name),
+                        size
+                    )?;
+                }
+            }
+        }
+        Block::Citation { title, url, text } => {
+            writeln!(html, "<figure class=\"citation\">")?;
+            if let Some(title) = title {
+                let title = match url {
+                    Some(url) => link(url, &escape(title)),
+                    None => escape(title),
+                };
+                writeln!(html, "<figcaption>Quoted Content: {}</figcaption>", title)?;
+            }
+            writeln!(html, "<blockquote>{}</blockquote>", markdown_to_html(text))?;
+            writeln!(html, "</figure>")?;
+        }
+        Block::Sources(sources) => {
+            writeln!(
+                html,
+                "<div class=\"block-title\">Sources</div>\n<ol class=\"sources\">"
+            )?;
+            for source in sources {
+                let title = source.title.as_deref().unwrap_or(&source.url);
+                write!(
+                    html,
+                    "<li id=\"source-{}\" value=\"{}\">{}",
+                    source.number,
+                    source.number,
+                    link(&source.url, &escape(title))
+                )?;
+                match &source.domain {
+                    Some(domain) => writeln!(html, " ({})</li>", escape(domain))?,
+                    None => writeln!(html, "</li>")?,
+                }
+            }
+            writeln!(html, "</ol>")?;
+        }
+        Block::Browsing(pages) => {
+            if collapse_browsing {
+                writeln!(
+                    html,
+                    "<details class=\"browsing\">\n<summary>Browsed Pages ({})</summary>\n<ul>",
+                    pages.len()
+                )?;
+            } else {
+                writeln!(html, "<div class=\"block-title\">Browsed Pages</div>\n<ul>")?;
+            }
+            for page in pages {
+                write_page(html, page)?;
+            }
+            writeln!(html, "</ul>")?;
+            if collapse_browsing {
+                writeln!(html, "</details>")?;
+            }
+        }
+        Block::ToolCall { .. } => {
+            // Tool calls are kept in the model but not rendered
+        }
+    }
+    Ok(())
+}
+
+fn write_turn(html: &mut String, turn: &TurnView, collapse_browsing: bool) -> Result<()> {
+    let class = match turn.turn.role {
+        Role::User => "user",
+        _ => "assistant",
+    };
+    let header = &turn.sections[0];
+
+    if let Some((previous, model)) = turn.model_switch {
+        writeln!(
+            html,
+            "<p class=\"model-switch\">Model switched from {} to {}</p>",
+            escape(previous),
+            escape(model)
+        )?;
+    }
+
+    let model = header
+        .model
+        .map(|model| format!("<span class=\"model\">{}</span>", escape(model)))
+        .unwrap_or_default();
+    writeln!(html, "<article class=\"turn {}\">", class)?;
+    writeln!(
+        html,
+        "<header class=\"turn-header\"><span class=\"sender\">{}</span>{}<time>{}</time></header>",
+        escape(header.sender),
+        model,
+        escape(&header.time)
+    )?;
+
+    // Thinking is collapsed so the answer stays readable
+    for section in &turn.sections {
+        if section.thinking {
+            writeln!(
+                html,
+                "<details class=\"thinking\">\n<summary>Thinking Process</summary>\n<div class=\"body\">"
+            )?;
+        }
+        for block in &section.blocks {
+            write_block(html, block, collapse_browsing)?;
+        }
+        if section.thinking {
+            writeln!(html, "</div>\n</details>")?;
+        }
+    }
+
+    writeln!(html, "</article>")?;
+    Ok(())
+}
+
+pub(crate) fn write(view: &ConversationView) -> Result<String> {
+    let mut html = String::with_capacity(view.turns.len() * 1000);
+
+    writeln!(html, "<!DOCTYPE html>")?;
+    writeln!(html, "<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">")?;
+    writeln!(
+        html,
+        "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">"
+    )?;
+    writeln!(html, "<title>{}</title>", escape(&view.title))?;
+    writeln!(html, "<style>{}</style>", STYLE)?;
+    writeln!(html, "</head>\n<body>\n<main>")?;
+
+    // Handle chat title and times
+    writeln!(html, "<h1>{}</h1>", escape(&view.title))?;
+    writeln!(html, "<dl class=\"meta\">")?;
+    writeln!(html, "<dt>Platform</dt><dd>{}</dd>", escape(view.platform))?;
+    if let Some(project) = view.project {
+        writeln!(html, "<dt>Project</dt><dd>{}</dd>", escape(project))?;
+    }
+    match view.models.as_slice() {
+        _ if !view.model_headers => {}
+        [] => {}
+        [model] => writeln!(html, "<dt>Model</dt><dd>{}</dd>", escape(model))?,
+        models => writeln!(
+            html,
+            "<dt>Models</dt><dd>{}</dd>",
+            escape(&models.join(", "))
+        )?,
+    }
+    writeln!(
+        html,
+        "<dt>First Message</dt><dd>{}</dd>",
+        escape(&view.first_message)
+    )?;
+    writeln!(
+        html,
+        "<dt>Last Message</dt><dd>{}</dd>",
+        escape(&view.last_message)
+    )?;
+    writeln!(html, "</dl>")?;
+
+    // Hidden messages come first, set apart from the conversation
+    if !view.hidden_context.is_empty() {
+        writeln!(
+            html,
+            "<section class=\"hidden-context\">\n<h2>Hidden Context</h2>"
+        )?;
+        writeln!(
+            html,
+            "<p class=\"hidden-note\">Not shown in the conversation</p>"
+        )?;
+        for turn in &view.hidden_context {
+            write_turn(&mut html, turn, view.collapse_browsing)?;
+        }
+        writeln!(html, "</section>")?;
+    }
+
+    // Process the turns
+    for turn in &view.turns {
+        write_turn(&mut html, turn, view.collapse_browsing)?;
+    }
+
+    // Alternate branches follow the exported path
+    if !view.branches.is_empty() {
+        writeln!(
+            html,
+            "<section class=\"branches\">\n<h2>Alternate Branches</h2>"
+        )?;
+        for branch in &view.branches {
+            writeln!(
+                html,
+                "<section class=\"branch\">\n<h3>Branch {}</h3>",
+                branch.number
+            )?;
+            match &branch.forks_after {
+                Some(label) => {
+                    writeln!(html, "<p class=\"fork\">Forks after {}</p>", escape(label))?
+                }
+                None => writeln!(
+                    html,
+                    "<p class=\"fork\">Forks at the start of the conversation</p>"
+                )?,
+            }
+            for turn in &branch.turns {
+                write_turn(&mut html, turn, view.collapse_browsing)?;
+            }
+            writeln!(html, "</section>")?;
+        }
+        writeln!(html, "</section>")?;
+    }
+
+    writeln!(html, "</main>\n</body>\n</html>")?;
+    Ok(html)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_web_and_mail_links_are_kept() {
+        assert!(is_safe_url("https://example.com/a:b"));
+        assert!(is_safe_url("mailto:someone@example.com"));
+        assert!(is_safe_url("#source-1"));
+        assert!(is_safe_url("chat_assets/image.png"));
+        assert!(!is_safe_url("javascript:alert(1)"));
+        assert!(!is_safe_url(" JavaScript:alert(1)"));
+        assert!(!is_safe_url("java\tscript:alert(1)"));
+        assert!(!is_safe_url("data:text/html,hi"));
+    }
+
+    #[test]
+    fn unsafe_links_keep_their_text() {
+        let html = markdown_to_html("[a](javascript:x) [b](https://b.example) ![c](vbscript:y)");
+        assert_eq!(html, "<p>a <a href=\"https://b.example\">b</a> c</p>\n");
+    }
+}
@@ -0,0 +1,370 @@
+use crate::config::MarkdownConfig;
+use crate::parser::conversation::{Block, Conversation, Turn};
+use crate::parser::error::Result;
+use crate::parser::participant::ParticipantMapper;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Bumped whenever a field is renamed or removed, not when one is added.
+const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// The top-level object of the normalized JSON export. Timestamps are
+/// RFC 3339 strings in UTC; optional fields are `null` when the platform
+/// does not provide them.
+#[derive(Serialize)]
+struct Document<'a> {
+    schema_version: u32,
+    /// Conversation id as given by the platform
+    id: Option<&'a str>,
+    title: String,
+    /// "Claude", "ChatGPT" or "DeepSeek"
+    platform: &'static str,
+    project: Option<Project<'a>>,
+    created_at: String,
+    updated_at: String,
+    /// Each role that appears in the messages, with its display name
+    participants: Vec<Participant<'a>>,
+    /// Messages the platform hides from the conversation, only present
+    /// when hidden context is enabled
+    hidden_context: Vec<Message<'a>>,
+    /// The exported path, in order
+    messages: Vec<Message<'a>>,
+    /// Alternate branches, only present when branches are enabled
+    branches: Vec<BranchDocument<'a>>,
+}
+
+#[derive(Serialize)]
+struct Project<'a> {
+    id: Option<&'a str>,
+    name: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct Participant<'a> {
+    role: &'a str,
+    name: &'a str,
+}
+
+#[derive(Serialize)]
+struct Message<'a> {
+    id: Option<&'a str>,
+    /// "user", "assistant", "system", "tool" or a platform-specific role
+    role: &'a str,
+    created_at: Option<String>,
+    /// Model slug, e.g. "gpt-4o", when the platform records it
+    model: Option<&'a str>,
+    blocks: Vec<BlockDocument<'a>>,
+}
+
+#[derive(Serialize)]
+struct BranchDocument<'a> {
+    /// Id of the message the branch continues from, `null` if it
+    /// starts the conversation
+    forks_after: Option<&'a str>,
+    messages: Vec<Message<'a>>,
+}
+
+/// Message content, tagged by `type`.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum BlockDocument<'a> {
+    Text {
+        text: &'a str,
+    },
+    Thinking {
+        text: &'a str,
+        summaries: &'a [String],
+        started_at: Option<String>,
+    },
+    Artifact {
+        id: Option<&'a str>,
+        title: Option<&'a str>,
+        language: Option<&'a str>,
+        /// MIME type, e.g. "application/vnd.ant.code"
+        kind: Option<&'a str>,
+        /// "create", "update" or "rewrite"
+        command: Option<&'a str>,
+        /// The whole artifact as of this message, with updates applied
+        content: &'a str,
+    },
+    Execution {
+        language: Option<&'a str>,
+        code: &'a str,
+        stdout: Option<&'a str>,
+        stderr: Option<&'a str>,
+        /// File ids of the images the run generated
+        files: &'a [String],
+    },
+    Image {
+        /// The platform's file id
+        asset: &'a str,
+        width: Option<u32>,
+        height: Option<u32>,
+        size_bytes: Option<u64>,
+        prompt: Option<&'a str>,
+        /// Where the image was copied, relative to the output file
+        path: Option<&'a str>,
+    },
+    Attachment {
+        name: &'a str,
+        content: Option<&'a str>,
+        size_bytes: Option<u64>,
+    },
+    Citation {
+        title: Option<&'a str>,
+        url: Option<&'a str>,
+        text: &'a str,
+    },
+    ToolCall {
+        name: &'a str,
+        input: Option<&'a Value>,
+    },
+    /// Pages cited in the message, which its text refers to as `[^number]`
+    Sources {
+        sources: Vec<SourceDocument<'a>>,
+    },
+    /// Pages a browsing or search tool visited or found for the message
+    Browsing {
+        pages: Vec<PageDocument<'a>>,
+    },
+}
+
+#[derive(Serialize)]
+struct PageDocument<'a> {
+    title: Option<&'a str>,
+    url: Option<&'a str>,
+    domain: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct SourceDocument<'a> {
+    number: usize,
+    title: Option<&'a str>,
+    url: &'a str,
+    domain: Option<&'a str>,
+}
+
+fn timestamp(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// Thinking, code runs and attachments follow their options, as they do in the other
+/// formats.
+fn block_document<'a>(block: &'a Block, config: &MarkdownConfig) -> Option<BlockDocument<'a>> {
+    Some(match block {
+        Block::Text(text) if text.is_empty() => return None,
+        Block::Text(text) => BlockDocument::Text { text },
+        Block::Thinking { .. } if !config.reasoning => return None,
+        Block::Thinking {
+            text,
+            summaries,
+            started_at,
+        } => BlockDocument::Thinking {
+            text,
+            summaries,
+            started_at: started_at.as_ref().map(timestamp),
+        },
+        Block::Execution { .. } if !config.code_execution => return None,
+        Block::Execution {
+            language,
+            code,
+            stdout,
+            stderr,
+            files,
+        } => BlockDocument::Execution {
+            language: language.as_deref(),
+            code,
+            stdout: stdout.as_deref(),
+            stderr: stderr.as_deref(),
+            files,
+        },
+        Block::Image {
+            asset,
+            width,
+            height,
+            size_bytes,
+            prompt,
+            path,
+        } => BlockDocument::Image {
+            asset,
+            width: *width,
+            height: *height,
+            size_bytes: *size_bytes,
+            prompt: prompt.as_deref(),
+            path: path.as_deref(),
+        },
+        Block::Attachment { .. } if !config.attachments => return None,
+        Block::Artifact {
+            id,
+            title,
+            language,
+            kind,
+            command,
+            content,
+        } => BlockDocument::Artifact {
+            id: id.as_deref(),
+            title: title.as_deref(),
+            language: language.as_deref(),
+            kind: kind.as_deref(),
+            command: command.as_deref(),
+            content,
+        },
+        Block::Attachment {
+            name,
+            content,
+            size_bytes,
+        } => BlockDocument::Attachment {
+            name,
+            content: content.as_deref(),
+            size_bytes: *size_bytes,
+        },
+        Block::Citation { title, url, text } => BlockDocument::Citation {
+            title: title.as_deref(),
+            url: url.as_deref(),
+            text,
+        },
+        Block::ToolCall { name, input } => BlockDocument::ToolCall {
+            name,
+            input: input.as_ref(),
+        },
+        Block::Sources(sources) => BlockDocument::Sources {
+            sources: sources
+                .iter()
+                .map(|source| SourceDocument {
+                    number: source.number,
+                    title: source.title.as_deref(),
+                    url: &source.url,
+                    domain: source.domain.as_deref(),
+                })
+                .collect(),
+        },
+        Block::Browsing(pages) => BlockDocument::Browsing {
+            pages: pages
+                .iter()
+                .map(|page| PageDocument {
+                    title: page.title.as_deref(),
+                    url: page.url.as_deref(),
+                    domain: page.domain.as_deref(),
+                })
+                .collect(),
+        },
+    })
+}
+
+/// Returns `None` for turns left without content, such as thinking-only
+/// turns when reasoning is disabled.
+fn message<'a>(turn: &'a Turn, config: &MarkdownConfig) -> Option<Message<'a>> {
+    let blocks: Vec<_> = turn
+        .blocks
+        .iter()
+        .filter_map(|block| block_document(block, config))
+        .collect();
+    if blocks.is_empty() {
+        return None;
+    }
+
+    Some(Message {
+        id: turn.id.as_deref(),
+        role: turn.role.name(),
+        created_at: turn.created_at.as_ref().map(timestamp),
+        model: turn.model.as_deref(),
+        blocks,
+    })
+}
+
+pub(crate) fn write(
+    conversation: &Conversation,
+    config: &MarkdownConfig,
+    mut on_turn: impl FnMut(),
+) -> Result<String> {
+    let participant_mapper = ParticipantMapper::new(config, conversation.source);
+
+    let messages: Vec<_> = conversation
+        .turns
+        .iter()
+        .filter_map(|turn| {
+            on_turn();
+            message(turn, config)
+        })
+        .collect();
+
+    let hidden_context = conversation
+        .hidden_context
+        .iter()
+        .filter_map(|turn| message(turn, config))
+        .collect();
+
+    let branches: Vec<_> = if config.branches {
+        conversation
+            .branches
+            .iter()
+            .map(|branch| BranchDocument {
+                forks_after: branch.forks_after.as_deref(),
+                messages: branch
+                    .turns
+                    .iter()
+                    .filter_map(|turn| message(turn, config))
+                    .collect(),
+            })
+            .filter(|branch| !branch.messages.is_empty())
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    // Participants in order of first appearance
+    let mut participants: Vec<Participant> = Vec::new();
+    let branch_turns = conversation
+        .branches
+        .iter()
+        .filter(|_| config.branches)
+        .flat_map(|branch| &branch.turns);
+    let turns = conversation
+        .hidden_context
+        .iter()
+        .chain(&conversation.turns)
+        .chain(branch_turns);
+    for turn in turns {
+        let role = turn.role.name();
+        if !participants
+            .iter()
+            .any(|participant| participant.role == role)
+        {
+            participants.push(Participant {
+                role,
+                name: participant_mapper.get_name(&turn.role),
+            });
+        }
+    }
+
+    let project = if conversation.project_id.is_some() || conversation.project_name.is_some() {
+        Some(Project {
+            id: conversation.project_id.as_deref(),
+            name: conversation.project_name.as_deref(),
+        })
+    } else {
+        None
+    };
+
+    let document = Document {
+        schema_version: JSON_SCHEMA_VERSION,
+        id: conversation.id.as_deref(),
+        title: config
+            .title
+            .clone()
+            .unwrap_or_else(|| conversation.title.clone()),
+        platform: conversation.source.platform_name(),
+        project,
+        created_at: timestamp(&conversation.created_at),
+        updated_at: timestamp(&conversation.updated_at),
+        participants,
+        hidden_context,
+        messages,
+        branches,
+    };
+
+    let mut json = serde_json::to_string_pretty(&document)?;
+    json.push('\n');
+    Ok(json)
+}
@@ -0,0 +1,852 @@
+use crate::config::MarkdownConfig;
+use crate::parser::artifact::{artifact_file_name, artifact_label};
+use crate::parser::conversation::{Block, Conversation, Page, Source};
+use crate::parser::convert::{convert_chatgpt, convert_claude, convert_deepseek};
+use crate::parser::error::{ParseError, Result};
+use crate::parser::model::{ChatGPTChat, ClaudeChat, DeepSeekResponse};
+use crate::parser::render::{build_view, image_placeholder, ConversationView, TurnView};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+/// Hidden anchors written in update mode: one with the conversation id
+/// under the title, and one with the message id ahead of every turn.
+static ANCHOR_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"<!-- aichatmd:(conversation|message) (\S+) -->").unwrap());
+
+/// Marks where the turns of the exported path end in update mode, which
+/// is where later turns are inserted.
+const END_ANCHOR: &str = "<!-- aichatmd:end -->";
+
+pub fn parse_claude_to_markdown(chat: &ClaudeChat, config: &MarkdownConfig) -> Result<String> {
+    render(&convert_claude(chat, config)?, config)
+}
+
+pub fn parse_chatgpt_to_markdown(chat: &ChatGPTChat, config: &MarkdownConfig) -> Result<String> {
+    render(&convert_chatgpt(chat, config)?, config)
+}
+
+pub fn parse_deepseek_to_markdown(
+    response: &DeepSeekResponse,
+    config: &MarkdownConfig,
+) -> Result<String> {
+    render(&convert_deepseek(response, config)?, config)
+}
+
+/// Picks a backtick fence longer than any backtick run in the content,
+/// so nested code blocks never terminate the outer one early.
+pub(crate) fn fence_for(content: &str) -> String {
+    let longest_run = content.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest_run.max(2) + 1)
+}
+
+fn write_fenced(markdown: &mut String, language: Option<&str>, content: &str) -> Result<()> {
+    let fence = fence_for(content);
+    writeln!(markdown, "{}{}", fence, language.unwrap_or_default())?;
+    writeln!(markdown, "{}", content)?;
+    writeln!(markdown, "{}\n", fence)?;
+    Ok(())
+}
+
+fn write_anchor(markdown: &mut String, kind: &str, id: &str) -> Result<()> {
+    writeln!(markdown, "<!-- aichatmd:{} {} -->\n", kind, id)?;
+    Ok(())
+}
+
+/// Identifies each turn of the exported path by its message id. The few
+/// turns a platform gives no id, such as ChatGPT reasoning that led to no
+/// answer, are named after the turn before them, which stays the same as
+/// the conversation grows.
+fn turn_anchors(turns: &[TurnView]) -> Vec<String> {
+    let mut anchors = Vec::with_capacity(turns.len());
+    let mut parent = "start";
+    let mut without_id = 0;
+    for turn in turns {
+        match turn.turn.id.as_deref() {
+            Some(id) => {
+                parent = id;
+                without_id = 0;
+                anchors.push(id.to_string());
+            }
+            None => {
+                without_id += 1;
+                anchors.push(format!("after-{}-{}", parent, without_id));
+            }
+        }
+    }
+    anchors
+}
+
+/// Markdown dialect to write. Obsidian adds YAML frontmatter in place of
+/// the header lines and uses callouts for thinking and attachments.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Flavor {
+    Plain,
+    Obsidian,
+}
+
+/// How blocks are written: the dialect, the directory artifact files are
+/// linked from, if they are written next to the output, the branch being
+/// written, whose artifacts have files of their own, and whether browsed
+/// pages are folded away.
+#[derive(Clone, Copy)]
+struct Style<'a> {
+    flavor: Flavor,
+    artifact_dir: Option<&'a str>,
+    branch: Option<usize>,
+    collapse_browsing: bool,
+}
+
+impl<'a> Style<'a> {
+    fn new(flavor: Flavor, view: &ConversationView<'a>) -> Self {
+        Self {
+            flavor,
+            artifact_dir: view.artifact_dir,
+            branch: None,
+            collapse_browsing: view.collapse_browsing,
+        }
+    }
+}
+
+/// Writes `content` as an Obsidian callout, quoting every line.
+fn write_callout(markdown: &mut String, header: &str, content: &str) -> Result<()> {
+    writeln!(markdown, "> {}", header)?;
+    for line in content.trim_end().lines() {
+        if line.is_empty() {
+            writeln!(markdown, ">")?;
+        } else {
+            writeln!(markdown, "> {}", line)?;
+        }
+    }
+    writeln!(markdown)?;
+    Ok(())
+}
+
+/// Writes the footnote definition of a cited page.
+fn write_source(markdown: &mut String, source: &Source) -> Result<()> {
+    write!(markdown, "[^{}]: ", source.number)?;
+    match &source.title {
+        Some(title) => {
+            let title = title.replace('[', "\\[").replace(']', "\\]");
+            write!(markdown, "[{}](<{}>)", title, source.url)?
+        }
+        None => write!(markdown, "<{}>", source.url)?,
+    }
+    match &source.domain {
+        Some(domain) => writeln!(markdown, " ({})", domain)?,
+        None => writeln!(markdown)?,
+    }
+    Ok(())
+}
+
+/// Writes a browsed page as a list item, linked when its URL is known.
+fn write_page(markdown: &mut String, page: &Page) -> Result<()> {
+    let label = page
+        .title
+        .as_deref()
+        .or(page.url.as_deref())
+        .or(page.domain.as_deref())
+        .unwrap_or_default()
+        .replace('[', "\\[")
+        .replace(']', "\\]");
+    match &page.url {
+        Some(url) => write!(markdown, "- [{}](<{}>)", label, url)?,
+        None => write!(markdown, "- {}", label)?,
+    }
+    match &page.domain {
+        Some(domain) if page.title.is_some() || page.url.is_some() => {
+            writeln!(markdown, " ({})", domain)?
+        }
+        _ => writeln!(markdown)?,
+    }
+    Ok(())
+}
+
+fn write_block(markdown: &mut String, block: &Block, style: Style) -> Result<()> {
+    match block {
+        Block::Text(text) => {
+            writeln!(markdown, "{}\n", text)?;
+        }
+        Block::Thinking {
+            text, summaries, ..
+        } => {
+            writeln!(markdown, "{}\n", text)?;
+            // Show any provided summaries
+            if !summaries.is_empty() {
+                writeln!(markdown, "##### Thinking Summaries\n")?;
+                for (i, summary) in summaries.iter().enumerate() {
+                    writeln!(markdown, "{}. {}\n", i + 1, summary)?;
+                }
+            }
+        }
+        Block::Artifact {
+            id,
+            title,
+            language,
+            kind,
+            command,
+            content,
+        } => {
+            if let Some(label) = artifact_label(id.as_deref(), title.as_deref(), command.as_deref())
+            {
+                writeln!(markdown, "#### Artifact: {}\n", label)?;
+            }
+            // Artifacts written to files are linked instead of repeated
+            match (style.artifact_dir, id) {
+                (Some(dir), Some(id)) => {
+                    let file_name =
+                        artifact_file_name(id, style.branch, kind.as_deref(), language.as_deref());
+                    match style.flavor {
+                        Flavor::Obsidian => writeln!(markdown, "[[{}/{}]]\n", dir, file_name)?,
+                        Flavor::Plain => {
+                            writeln!(markdown, "[{}](<{}/{}>)\n", file_name, dir, file_name)?
+                        }
+                    }
+                }
+                _ => write_fenced(markdown, language.as_deref(), content)?,
+            }
+        }
+        Block::Execution {
+            language,
+            code,
+            stdout,
+            stderr,
+            files,
+        } => {
+            write_fenced(markdown, language.as_deref(), code)?;
+            if let Some(stdout) = stdout {
+                writeln!(markdown, "##### Output\n")?;
+                write_fenced(markdown, None, stdout)?;
+            }
+            if let Some(stderr) = stderr {
+                writeln!(markdown, "##### Error\n")?;
+                write_fenced(markdown, None, stderr)?;
+            }
+            if !files.is_empty() {
+                writeln!(markdown, "##### Generated Files\n")?;
+                for file in files {
+                    writeln!(markdown, "- `{}`", file)?;
+                }
+                writeln!(markdown)?;
+            }
+        }
+        Block::Image {
+            asset,
+            width,
+            height,
+            size_bytes,
+            prompt,
+            path,
+        } => {
+            match (path, style.flavor) {
+                (Some(path), Flavor::Obsidian) => writeln!(markdown, "![[{}]]\n", path)?,
+                (Some(path), Flavor::Plain) => writeln!(markdown, "![Image](<{}>)\n", path)?,
+                (None, _) => {
+                    let placeholder = image_placeholder(asset, *width, *height, *size_bytes);
+                    writeln!(markdown, "*[{}]*\n", placeholder)?
+                }
+            }
+            if let Some(prompt) = prompt {
+                writeln!(markdown, "*Prompt: {}*\n", prompt.trim())?;
+            }
+        }
+        Block::Attachment {
+            name,
+            content,
+            size_bytes,
+        } if style.flavor == Flavor::Obsidian => {
+            let label = match size_bytes {
+                Some(size) => format!("{} ({} bytes)", name, size),
+                None => name.clone(),
+            };
+            match content {
+                Some(content) => {
+                    let mut body = String::new();
+                    write_fenced(&mut body, None, content)?;
+                    write_callout(markdown, &format!("[!file]- {}", label), &body)?;
+                }
+                None => writeln!(markdown, "> [!file] {}\n", label)?,
+            }
+        }
+        Block::Attachment {
+            name,
+            content,
+            size_bytes,
+        } => {
+            writeln!(markdown, "#### Attachment: {}\n", name)?;
+            match (content, size_bytes) {
+                (Some(content), _) => write_fenced(markdown, None, content)?,
+                (None, Some(size)) => writeln!(markdown, "Size: {} bytes\n", size)?,
+                (None, None) => {}
+            }
+        }
+        Block::Citation { title, text, .. } => {
+            if let Some(title) = title {
+                writeln!(markdown, "##### Quoted Content: {}\n", title)?;
+            }
+            write_fenced(markdown, None, text)?;
+        }
+        Block::Sources(sources) => {
+            writeln!(markdown, "##### Sources\n")?;
+            for source in sources {
+                write_source(markdown, source)?;
+            }
+            writeln!(markdown)?;
+        }
+        Block::Browsing(pages) => {
+            let mut list = String::new();
+            for page in pages {
+                write_page(&mut list, page)?;
+            }
+            let summary = format!("Browsed Pages ({})", pages.len());
+            match (style.flavor, style.collapse_browsing) {
+                (Flavor::Obsidian, true) => {
+                    write_callout(markdown, &format!("[!search]- {}", summary), &list)?
+                }
+                (Flavor::Obsidian, false) => {
+                    write_callout(markdown, &format!("[!search] {}", summary), &list)?
+                }
+                (Flavor::Plain, true) => {
+                    writeln!(markdown, "<details>\n<summary>{}</summary>\n", summary)?;
+                    writeln!(markdown, "{}\n</details>\n", list)?;
+                }
+                (Flavor::Plain, false) => {
+                    writeln!(markdown, "##### Browsed Pages\n")?;
+                    writeln!(markdown, "{}", list)?;
+                }
+            }
+        }
+        Block::ToolCall { .. } => {
+            // Tool calls are kept in the model but not rendered
+        }
+    }
+    Ok(())
+}
+
+fn write_turn(markdown: &mut String, turn: &TurnView, style: Style) -> Result<()> {
+    if let Some((previous, model)) = turn.model_switch {
+        writeln!(
+            markdown,
+            "*Model switched from {} to {}*\n",
+            previous, model
+        )?;
+    }
+
+    // Thinking segments get their own header, and the answer
+    // that follows them is separated with a fresh header
+    for (i, section) in turn.sections.iter().enumerate() {
+        if i > 0 {
+            writeln!(markdown, "---\n")?;
+        }
+        match section.model {
+            Some(model) => writeln!(
+                markdown,
+                "#### {} ({}) @ {}\n",
+                section.sender, model, section.time
+            )?,
+            None => writeln!(markdown, "#### {} @ {}\n", section.sender, section.time)?,
+        }
+
+        if section.thinking && style.flavor == Flavor::Obsidian {
+            let mut thinking = String::new();
+            for block in &section.blocks {
+                write_block(&mut thinking, block, style)?;
+            }
+            write_callout(markdown, "[!thinking]- Thinking Process", &thinking)?;
+            continue;
+        }
+
+        if section.thinking {
+            writeln!(markdown, "##### Thinking Process\n")?;
+        }
+        for block in &section.blocks {
+            write_block(markdown, block, style)?;
+        }
+    }
+
+    writeln!(markdown, "---\n")?;
+    Ok(())
+}
+
+fn write_body(markdown: &mut String, view: &ConversationView, flavor: Flavor) -> Result<()> {
+    let style = Style::new(flavor, view);
+
+    // Hidden messages come first, set apart from the conversation
+    if !view.hidden_context.is_empty() {
+        writeln!(markdown, "## Hidden Context\n")?;
+        writeln!(markdown, "*Not shown in the conversation*\n")?;
+        writeln!(markdown, "---\n")?;
+        for turn in &view.hidden_context {
+            write_turn(markdown, turn, style)?;
+        }
+        writeln!(markdown, "## Conversation\n")?;
+    }
+
+    // Process the turns
+    let anchors = turn_anchors(&view.turns);
+    for (turn, anchor) in view.turns.iter().zip(&anchors) {
+        if view.anchors {
+            write_anchor(markdown, "message", anchor)?;
+        }
+        write_turn(markdown, turn, style)?;
+    }
+    if view.anchors {
+        writeln!(markdown, "{}\n", END_ANCHOR)?;
+    }
+
+    // Alternate branches follow the exported path
+    if !view.branches.is_empty() {
+        writeln!(markdown, "## Alternate Branches\n")?;
+
+        for branch in &view.branches {
+            writeln!(markdown, "### Branch {}\n", branch.number)?;
+            match &branch.forks_after {
+                Some(label) => writeln!(markdown, "*Forks after {}*\n", label)?,
+                None => writeln!(markdown, "*Forks at the start of the conversation*\n")?,
+            }
+            writeln!(markdown, "---\n")?;
+
+            let style = Style {
+                branch: Some(branch.number),
+                ..style
+            };
+            for turn in &branch.turns {
+                write_turn(markdown, turn, style)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn write(view: &ConversationView) -> Result<String> {
+    let mut markdown = String::with_capacity(view.turns.len() * 500);
+
+    // Handle chat title and times
+    writeln!(markdown, "# {}", view.title)?;
+    writeln!(markdown)?;
+    if let (true, Some(id)) = (view.anchors, view.id) {
+        write_anchor(&mut markdown, "conversation", id)?;
+    }
+    writeln!(markdown, "**Platform:** {}  ", view.platform)?;
+    if let Some(project) = view.project {
+        writeln!(markdown, "**Project:** {}  ", project)?;
+    }
+    for line in header_model_lines(view) {
+        writeln!(markdown, "{}", line)?;
+    }
+    writeln!(markdown, "**First Message:** {}  ", view.first_message)?;
+    writeln!(markdown, "{}", last_message_line(view))?;
+    writeln!(markdown, "\n---\n")?;
+
+    write_body(&mut markdown, view, Flavor::Plain)?;
+    Ok(markdown)
+}
+
+/// The header line naming the models, if they are shown.
+fn header_model_lines(view: &ConversationView) -> Vec<String> {
+    match view.models.as_slice() {
+        _ if !view.model_headers => Vec::new(),
+        [] => Vec::new(),
+        [model] => vec![format!("**Model:** {}  ", model)],
+        models => vec![format!("**Models:** {}  ", models.join(", "))],
+    }
+}
+
+fn last_message_line(view: &ConversationView) -> String {
+    format!("**Last Message:** {}  ", view.last_message)
+}
+
+/// Quotes a value for YAML. JSON strings are valid YAML scalars.
+fn yaml_string(value: &str) -> Result<String> {
+    Ok(serde_json::to_string(value)?)
+}
+
+/// The frontmatter `model` field: a single model, or a list of them.
+fn frontmatter_model_lines(view: &ConversationView) -> Result<Vec<String>> {
+    Ok(match view.models.as_slice() {
+        [] => Vec::new(),
+        [model] => vec![format!("model: {}", yaml_string(model)?)],
+        models => {
+            let mut lines = vec!["model:".to_string()];
+            for model in models {
+                lines.push(format!("  - {}", yaml_string(model)?));
+            }
+            lines
+        }
+    })
+}
+
+pub(crate) fn write_obsidian(view: &ConversationView) -> Result<String> {
+    let mut markdown = String::with_capacity(view.turns.len() * 500);
+
+    // Frontmatter that Dataview can query
+    writeln!(markdown, "---")?;
+    writeln!(markdown, "title: {}", yaml_string(&view.title)?)?;
+    writeln!(markdown, "platform: {}", yaml_string(view.platform)?)?;
+    if let Some(project) = view.project {
+        writeln!(markdown, "project: {}", yaml_string(project)?)?;
+    }
+    writeln!(markdown, "created: {}", view.created_at)?;
+    writeln!(markdown, "updated: {}", view.updated_at)?;
+    for line in frontmatter_model_lines(view)? {
+        writeln!(markdown, "{}", line)?;
+    }
+    writeln!(markdown, "tags:")?;
+    writeln!(markdown, "  - ai-chat")?;
+    writeln!(markdown, "  - {}", view.platform.to_lowercase())?;
+    writeln!(markdown, "messages: {}", view.turns.len())?;
+    if let Some(source_file) = view.source_file {
+        writeln!(markdown, "source: {}", yaml_string(source_file)?)?;
+    }
+    writeln!(markdown, "---\n")?;
+
+    writeln!(markdown, "# {}\n", view.title)?;
+    if let (true, Some(id)) = (view.anchors, view.id) {
+        write_anchor(&mut markdown, "conversation", id)?;
+    }
+
+    write_body(&mut markdown, view, Flavor::Obsidian)?;
+    Ok(markdown)
+}
+
+/// Rewrites a header field in place: the line starting with one of
+/// `keys` and, for a YAML list, the item lines under it. A field that is
+/// not there yet is added ahead of the line starting with `before`, or
+/// at the end. Without `lines` the field is removed.
+fn set_header_field(header: &str, keys: &[&str], lines: &[String], before: Option<&str>) -> String {
+    let mut result: Vec<&str> = Vec::new();
+    let mut written = false;
+    let mut in_field = false;
+
+    for line in header.lines() {
+        if keys.iter().any(|key| line.starts_with(key)) {
+            if !written {
+                result.extend(lines.iter().map(String::as_str));
+                written = true;
+            }
+            in_field = true;
+            continue;
+        }
+        if in_field && line.starts_with("  - ") {
+            continue;
+        }
+        in_field = false;
+        if !written && before.is_some_and(|before| line.starts_with(before)) {
+            result.extend(lines.iter().map(String::as_str));
+            written = true;
+        }
+        result.push(line);
+    }
+    if !written {
+        result.extend(lines.iter().map(String::as_str));
+    }
+
+    let mut rewritten = result.join("\n");
+    if header.ends_with('\n') {
+        rewritten.push('\n');
+    }
+    rewritten
+}
+
+/// Brings the header of an earlier export up to date with the turns
+/// added to it: the last message time and models, or for Obsidian the
+/// `updated`, `model` and `messages` frontmatter fields.
+fn refresh_header(markdown: &str, view: &ConversationView, flavor: Flavor) -> Result<String> {
+    // The header ends at the first rule, which closes the frontmatter
+    let start = match flavor {
+        Flavor::Plain => 0,
+        Flavor::Obsidian if markdown.starts_with("---\n") => 4,
+        Flavor::Obsidian => return Ok(markdown.to_string()),
+    };
+    let Some(end) = markdown[start..].find("\n---\n").map(|end| start + end) else {
+        return Ok(markdown.to_string());
+    };
+    let header = &markdown[start..end];
+
+    let header = match flavor {
+        Flavor::Plain => {
+            let header = set_header_field(
+                header,
+                &["**Last Message:**"],
+                &[last_message_line(view)],
+                None,
+            );
+            set_header_field(
+                &header,
+                &["**Model:**", "**Models:**"],
+                &header_model_lines(view),
+                Some("**First Message:**"),
+            )
+        }
+        Flavor::Obsidian => {
+            let header = set_header_field(
+                header,
+                &["updated:"],
+                &[format!("updated: {}", view.updated_at)],
+                Some("tags:"),
+            );
+            let header = set_header_field(
+                &header,
+                &["model:"],
+                &frontmatter_model_lines(view)?,
+                Some("tags:"),
+            );
+            set_header_field(
+                &header,
+                &["messages:"],
+                &[format!("messages: {}", view.turns.len())],
+                Some("source:"),
+            )
+        }
+    };
+
+    Ok(format!(
+        "{}{}{}",
+        &markdown[..start],
+        header,
+        &markdown[end..]
+    ))
+}
+
+/// Adds the turns of the exported path that an earlier export lacks
+/// where that path ends, ahead of any alternate branches and of notes
+/// added after it, and brings the header up to date. Everything else in
+/// the file is left as it was. Returns `None` if there is nothing new.
+fn append_new_turns(
+    existing: &str,
+    view: &ConversationView,
+    flavor: Flavor,
+) -> Result<Option<String>> {
+    let mut conversation = None;
+    let mut present = HashSet::new();
+    for anchor in ANCHOR_RE.captures_iter(existing) {
+        let id = anchor.get(2).map_or("", |id| id.as_str());
+        match &anchor[1] {
+            "conversation" => conversation = Some(id),
+            _ => {
+                present.insert(id);
+            }
+        }
+    }
+
+    match (conversation, view.id) {
+        (Some(existing_id), Some(id)) if existing_id == id => {}
+        (Some(_), Some(_)) => {
+            return Err(ParseError::Update(format!(
+                "'{}': the file holds a different conversation",
+                view.title
+            )))
+        }
+        (Some(_), None) => {
+            return Err(ParseError::Update(format!(
+                "'{}': the conversation has no id",
+                view.title
+            )))
+        }
+        (None, _) => {
+            return Err(ParseError::Update(format!(
+                "'{}': the file was not written in update mode",
+                view.title
+            )))
+        }
+    }
+
+    let style = Style::new(flavor, view);
+    let mut new_turns = String::new();
+    for (turn, anchor) in view.turns.iter().zip(turn_anchors(&view.turns)) {
+        if present.contains(anchor.as_str()) {
+            continue;
+        }
+        write_anchor(&mut new_turns, "message", &anchor)?;
+        write_turn(&mut new_turns, turn, style)?;
+    }
+    if new_turns.is_empty() {
+        return Ok(None);
+    }
+
+    // Files written before the end anchor get one, ahead of the
+    // alternate branches if there are any
+    let insert_at = match existing.find(END_ANCHOR) {
+        Some(end) => end,
+        None => {
+            writeln!(new_turns, "{}\n", END_ANCHOR)?;
+            existing
+                .find("\n## Alternate Branches\n")
+                .map_or(existing.len(), |branches| branches + 1)
+        }
+    };
+    let (before, after) = existing.split_at(insert_at);
+
+    let mut markdown = before.trim_end().to_string();
+    markdown.push_str("\n\n");
+    markdown.push_str(&new_turns);
+    markdown.push_str(after);
+
+    Ok(Some(refresh_header(&markdown, view, flavor)?))
+}
+
+pub(crate) fn update(existing: &str, view: &ConversationView) -> Result<Option<String>> {
+    append_new_turns(existing, view, Flavor::Plain)
+}
+
+pub(crate) fn update_obsidian(existing: &str, view: &ConversationView) -> Result<Option<String>> {
+    append_new_turns(existing, view, Flavor::Obsidian)
+}
+
+/// Renders the conversation as Markdown, whatever the configured format.
+pub fn render(conversation: &Conversation, config: &MarkdownConfig) -> Result<String> {
+    write(&build_view(conversation, config, || {}))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ChatSource, OutputFormat};
+    use crate::parser::conversation::{Role, Turn};
+    use crate::parser::convert::parse_conversation;
+
+    /// A Claude chat of `(uuid, parent, sender, text)` messages, a minute
+    /// apart, ending at `leaf`.
+    fn chat(leaf: &str, messages: &[(&str, &str, &str, &str)]) -> Conversation {
+        let messages: Vec<_> = messages
+            .iter()
+            .enumerate()
+            .map(|(i, (uuid, parent, sender, text))| {
+                format!(
+                    r#"{{"uuid": "{}", "parent_message_uuid": "{}", "sender": "{}",
+                        "created_at": "2025-01-01T10:{:02}:00Z",
+                        "content": [{{"type": "text", "text": "{}"}}]}}"#,
+                    uuid, parent, sender, i, text
+                )
+            })
+            .collect();
+        let json = format!(
+            r#"{{"uuid": "c1", "name": "Chat", "created_at": "2025-01-01T10:00:00Z",
+                "updated_at": "2025-01-01T10:{:02}:00Z", "current_leaf_message_uuid": "{}",
+                "chat_messages": [{}]}}"#,
+            messages.len() - 1,
+            leaf,
+            messages.join(",")
+        );
+        parse_conversation(&json, ChatSource::Claude, &config(OutputFormat::Markdown)).unwrap()
+    }
+
+    fn config(format: OutputFormat) -> MarkdownConfig {
+        MarkdownConfig {
+            format,
+            update: true,
+            branches: true,
+            ..MarkdownConfig::default()
+        }
+    }
+
+    const FIRST: &[(&str, &str, &str, &str)] = &[
+        ("u1", "root", "human", "Hi"),
+        ("a1", "u1", "assistant", "Hello"),
+        ("a1b", "u1", "assistant", "Hey"),
+    ];
+
+    fn continued() -> Conversation {
+        let mut messages = FIRST.to_vec();
+        messages.extend([
+            ("u2", "a1", "human", "Next"),
+            ("a2", "u2", "assistant", "Answer"),
+        ]);
+        chat("a2", &messages)
+    }
+
+    fn update_with(existing: &str, conversation: &Conversation, format: OutputFormat) -> String {
+        let config = config(format);
+        let view = build_view(conversation, &config, || {});
+        let updated = match format {
+            OutputFormat::Obsidian => update_obsidian(existing, &view),
+            _ => update(existing, &view),
+        };
+        updated.unwrap().expect("new turns")
+    }
+
+    #[test]
+    fn new_turns_go_where_the_conversation_ends() {
+        let config = config(OutputFormat::Markdown);
+        let first = write(&build_view(&chat("a1", FIRST), &config, || {})).unwrap();
+        let existing = format!("{}\nMY NOTE\n", first);
+
+        let updated = update_with(&existing, &continued(), OutputFormat::Markdown);
+        let position = |text: &str| updated.find(text).unwrap();
+        assert!(position("Hello") < position("<!-- aichatmd:message u2 -->"));
+        assert!(position("Answer") < position(END_ANCHOR));
+        assert!(position(END_ANCHOR) < position("## Alternate Branches"));
+        assert!(position("Hey") < position("MY NOTE"));
+        assert!(updated.contains("**Last Message:** 2025-01-01 10:04 AM UTC"));
+        assert_eq!(updated.matches(END_ANCHOR).count(), 1);
+    }
+
+    #[test]
+    fn nothing_is_written_without_new_turns() {
+        let config = config(OutputFormat::Markdown);
+        let conversation = continued();
+        let view = build_view(&conversation, &config, || {});
+        let existing = write(&view).unwrap();
+
+        assert!(update(&existing, &view).unwrap().is_none());
+    }
+
+    #[test]
+    fn files_without_an_end_anchor_get_one_ahead_of_the_branches() {
+        let config = config(OutputFormat::Markdown);
+        let first = write(&build_view(&chat("a1", FIRST), &config, || {})).unwrap();
+        let existing = first.replace(&format!("{}\n\n", END_ANCHOR), "");
+
+        let updated = update_with(&existing, &continued(), OutputFormat::Markdown);
+        assert!(updated.find("Answer").unwrap() < updated.find(END_ANCHOR).unwrap());
+        assert!(updated.find(END_ANCHOR).unwrap() < updated.find("## Alternate Branches").unwrap());
+    }
+
+    #[test]
+    fn obsidian_frontmatter_is_refreshed() {
+        let config = config(OutputFormat::Obsidian);
+        let existing = write_obsidian(&build_view(&chat("a1", FIRST), &config, || {})).unwrap();
+        assert!(existing.contains("messages: 2\n"));
+
+        let updated = update_with(&existing, &continued(), OutputFormat::Obsidian);
+        assert!(updated.contains("messages: 4\n"));
+        assert!(updated.contains("updated: 2025-01-01T10:04:00"));
+        assert!(!updated.contains("messages: 2\n"));
+    }
+
+    #[test]
+    fn another_conversation_is_not_merged() {
+        let config = config(OutputFormat::Markdown);
+        let existing = write(&build_view(&chat("a1", FIRST), &config, || {})).unwrap();
+        let mut other = continued();
+        other.id = Some("c2".to_string());
+
+        let view = build_view(&other, &config, || {});
+        assert!(matches!(
+            update(&existing, &view),
+            Err(ParseError::Update(_))
+        ));
+    }
+
+    #[test]
+    fn turns_without_an_id_are_anchored_to_the_turn_before() {
+        let turn = |id: Option<&str>| Turn {
+            id: id.map(str::to_string),
+            role: Role::Assistant,
+            created_at: None,
+            model: None,
+            blocks: vec![Block::Text("text".to_string())],
+        };
+        let mut conversation = continued();
+        conversation.turns = vec![turn(None), turn(Some("u1")), turn(None), turn(None)];
+
+        let config = config(OutputFormat::Markdown);
+        let view = build_view(&conversation, &config, || {});
+        assert_eq!(
+            turn_anchors(&view.turns),
+            ["after-start-1", "u1", "after-u1-1", "after-u1-2"]
+        );
+    }
+}
@@ -1,210 +0,0 @@
-use crate::config::MarkdownConfig;
-use crate::parser::error::Result;
-use crate::parser::model::{ChatGPTChat, ChatGPTContentPart, ChatGPTMessage, ChatGPTNode};
-use crate::parser::participant::ChatGPTParticipantMapper;
-use crate::parser::timestamp::{TimeFormat, TimeFormatter};
-use crate::parser::types::ChatGPTContentType;
-use indicatif::{ProgressBar, ProgressStyle};
-use once_cell::sync::Lazy;
-use regex::Regex;
-use std::fmt::Write;
-
-static CITATION_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s).*?").unwrap());
-
-fn strip_citations<S: AsRef<str>>(s: S) -> String {
-    CITATION_RE.replace_all(s.as_ref(), "").into_owned()
-}
-
-fn get_ordered_messages(chat: &ChatGPTChat) -> Vec<&ChatGPTNode> {
-    let mut messages = Vec::new();
-    let mut current_id = Some(chat.current_node.as_str());
-
-    // Walk backwards through the tree to collect messages in reverse order
-    while let Some(id) = current_id {
-        if let Some(node) = chat.mapping.get(id) {
-            if node.message.is_some() {
-                // Only collect nodes that have messages
-                messages.push(node);
-            }
-            current_id = node.parent.as_deref();
-        } else {
-            break;
-        }
-    }
-
-    // Reverse to get chronological order
-    messages.reverse();
-    messages
-}
-
-fn is_reasoning_message(message: &ChatGPTMessage) -> bool {
-    // Identifies ChatGPT reasoning/thinking messages by checking for:
-    // - Tool or system messages that have text content
-    // - Tool or system messages with initial text "Reasoning" and finished text "Reasoned..."
-    // - Tool or system messages with initial text "Thinking" and finished text "Thought..."
-    (message.author.role == "tool" || message.author.role == "system")
-        && (message.content.content_type == "text"
-            || (message.metadata.initial_text.as_deref() == Some("Reasoning")
-                && message
-                    .metadata
-                    .finished_text
-                    .as_deref()
-                    .is_some_and(|text| text.starts_with("Reasoned")))
-            || (message.metadata.initial_text.as_deref() == Some("Thinking")
-                && message
-                    .metadata
-                    .finished_text
-                    .as_deref()
-                    .is_some_and(|text| text.starts_with("Thought"))))
-}
-
-pub fn parse_to_markdown(chat: &ChatGPTChat, config: &MarkdownConfig) -> Result<String> {
-    let participant_mapper = ChatGPTParticipantMapper::new(config);
-    let time_formatter = TimeFormatter::new(&config.timezone, TimeFormat::Unix);
-
-    let messages = get_ordered_messages(chat);
-    let mut markdown = String::with_capacity(messages.len() * 500);
-
-    // Setup progress bar
-    let progress = ProgressBar::new(messages.len() as u64);
-    progress.set_style(
-        ProgressStyle::default_bar()
-            .progress_chars("##-")
-            .template("[{bar:40}] {pos}/{len} ({percent}%)")
-            .unwrap(),
-    );
-
-    // Setup reasoning handling
-    let mut in_reasoning_block = false;
-
-    // Handle chat title and times
-    let title = config.title.clone().unwrap_or_else(|| chat.title.clone());
-    let first_message_time = time_formatter.format_unix(chat.created_at)?;
-    let last_message_time = time_formatter.format_unix(chat.updated_at)?;
-
-    writeln!(markdown, "# {}", title)?;
-    writeln!(markdown)?;
-    writeln!(
-        markdown,
-        "**Platform:** {}  ",
-        config.chat_source.platform_name()
-    )?;
-    writeln!(markdown, "**First Message:** {}  ", first_message_time)?;
-    writeln!(markdown, "**Last Message:** {}  ", last_message_time)?;
-    writeln!(markdown, "\n---\n")?;
-
-    // Process messages
-    for node in messages {
-        progress.inc(1);
-
-        if let Some(message) = &node.message {
-            let content = &message.content;
-
-            // Skip if there are no text parts or all text parts are empty
-            if content.text.as_deref().unwrap_or_default().is_empty()
-                && (content.parts.is_empty()
-                    || content
-                        .parts
-                        .iter()
-                        .filter_map(|part| {
-                            if let ChatGPTContentPart::Text(s) = part {
-                                Some(s.trim())
-                            } else {
-                                None
-                            }
-                        })
-                        .all(|s| s.is_empty()))
-            {
-                continue;
-            }
-
-            // Skip 'code' content
-            if content.content_type == "code" {
-                continue;
-            }
-
-            let is_reasoning = config.reasoning && is_reasoning_message(message);
-
-            // Get timestamp for both reasoning and non-reasoning messages
-            let timestamp = if let Some(created_at) = message.created_at {
-                time_formatter.format_unix(created_at)?
-            } else {
-                "Unknown Time".to_string()
-            };
-            let sender = participant_mapper.get_name(&message.author.role);
-
-            // Handle transition into reasoning block
-            if is_reasoning && !in_reasoning_block {
-                writeln!(markdown, "#### {} @ {}\n", sender, timestamp)?;
-                writeln!(markdown, "##### Thinking Process\n")?;
-                in_reasoning_block = true;
-            }
-            // Handle transition out of reasoning block
-            else if !is_reasoning && in_reasoning_block {
-                writeln!(markdown, "---\n")?;
-                in_reasoning_block = false;
-            }
-
-            // Only add sender/timestamp header for non-reasoning messages
-            if !is_reasoning {
-                writeln!(markdown, "#### {} @ {}\n", sender, timestamp)?;
-            }
-
-            match ChatGPTContentType::from(content.content_type.as_str()) {
-                ChatGPTContentType::Text | ChatGPTContentType::MultimodalText => {
-                    for part in &content.parts {
-                        if let ChatGPTContentPart::Text(text) = part {
-                            writeln!(markdown, "{}\n", strip_citations(text).trim())?;
-                        }
-                    }
-                }
-                ChatGPTContentType::Code => {
-                    if let Some(text) = &content.text {
-                        writeln!(markdown, "```")?;
-                        writeln!(markdown, "{}", strip_citations(text).trim())?;
-                        writeln!(markdown, "```\n")?;
-                    }
-                }
-                ChatGPTContentType::TetherQuote => {
-                    if let Some(quoted) = &content.text {
-                        if let Some(title) = &content.title {
-                            writeln!(markdown, "##### Quoted Content: {}\n", title)?;
-                        }
-                        writeln!(markdown, "````")?;
-                        writeln!(markdown, "{}", strip_citations(quoted).trim())?;
-                        writeln!(markdown, "````\n")?;
-                    }
-                    continue;
-                }
-                ChatGPTContentType::UserEditableContext => {
-                    // Skip user profile/instructions
-                    continue;
-                }
-                ChatGPTContentType::Tool | ChatGPTContentType::System => {
-                    for part in &content.parts {
-                        if let ChatGPTContentPart::Text(text) = part {
-                            writeln!(markdown, "{}\n", strip_citations(text).trim())?;
-                        }
-                    }
-                }
-                ChatGPTContentType::Unknown(content_type) => {
-                    println!("Encountered unknown content type: {}", content_type);
-                    for part in &content.parts {
-                        if let ChatGPTContentPart::Text(text) = part {
-                            writeln!(markdown, "{}\n", text.trim())?;
-                        }
-                    }
-                }
-            }
-
-            // Only add separator for non-reasoning messages
-            if !is_reasoning {
-                writeln!(markdown, "---\n")?;
-            }
-        }
-    }
-
-    progress.finish();
-
-    Ok(markdown)
-}
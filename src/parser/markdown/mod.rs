@@ -1,7 +0,0 @@
-mod chatgpt;
-mod claude;
-mod deepseek;
-
-pub use chatgpt::parse_to_markdown as parse_chatgpt_to_markdown;
-pub use claude::parse_to_markdown as parse_claude_to_markdown;
-pub use deepseek::parse_to_markdown as parse_deepseek_to_markdown;
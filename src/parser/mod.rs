@@ -1,11 +1,31 @@
+mod artifact;
+mod bulk;
+mod conversation;
+mod convert;
+mod dataset;
+mod detect;
 mod error;
+mod html;
+mod json;
 mod markdown;
-mod model;
+pub mod model;
 mod participant;
+mod render;
 mod timestamp;
+mod tree;
 mod types;
 
+pub use artifact::artifact_file_name;
+pub use bulk::{apply_project_names, is_bulk_export, parse_bulk_value, parse_projects, BulkEntry};
+pub use conversation::{Block, Branch, Conversation, Page, Role, Source, Turn};
+pub use convert::{
+    convert_chatgpt, convert_claude, convert_deepseek, parse_conversation, parse_conversation_value,
+};
+pub use dataset::render_dataset;
+pub use detect::detect_source;
 pub use error::ParseError;
 pub use markdown::{
     parse_chatgpt_to_markdown, parse_claude_to_markdown, parse_deepseek_to_markdown,
+    render as render_markdown,
 };
+pub use render::{render, render_update, render_with_progress};
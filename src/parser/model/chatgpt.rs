@@ -2,7 +2,12 @@ use serde::Deserialize;
 
 #[derive(Deserialize)]
 pub struct ChatGPTChat {
-    pub title: String,
+    #[serde(default)]
+    pub conversation_id: Option<String>,
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
     #[serde(rename = "create_time")]
     pub created_at: f64,
     #[serde(rename = "update_time")]
@@ -30,9 +35,26 @@ pub struct ChatGPTMessage {
     pub content: ChatGPTContent,
     pub status: String,
     #[serde(default)]
+    pub recipient: Option<String>,
+    #[serde(default)]
     pub metadata: ChatGPTMetadata,
 }
 
+impl ChatGPTMessage {
+    /// The canvas call this message makes, if it creates or updates a
+    /// canvas document.
+    pub fn canvas_call(&self) -> Option<ChatGPTCanvasCall> {
+        match self.recipient.as_deref() {
+            Some("canmore.create_textdoc" | "canmore.update_textdoc") => self
+                .content
+                .text
+                .as_deref()
+                .and_then(|text| serde_json::from_str(text).ok()),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Deserialize)]
 #[allow(dead_code)]
 pub struct ChatGPTAuthor {
@@ -58,6 +80,16 @@ pub struct ChatGPTContent {
     pub parts: Vec<ChatGPTContentPart>,
     #[serde(default)]
     pub text: Option<String>,
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Custom instructions of a `user_editable_context` message
+    #[serde(default)]
+    pub user_profile: Option<String>,
+    #[serde(default)]
+    pub user_instructions: Option<String>,
+    /// Memories of a `model_editable_context` message
+    #[serde(default)]
+    pub model_set_context: Option<String>,
 }
 
 #[derive(Default, Deserialize)]
@@ -73,6 +105,160 @@ pub struct ChatGPTMetadata {
     pub finished_text: Option<String>,
     #[serde(default)]
     pub is_visually_hidden_from_conversation: bool,
+    #[serde(default)]
+    pub aggregate_result: Option<ChatGPTAggregateResult>,
+    #[serde(default)]
+    pub content_references: Option<Vec<ChatGPTContentReference>>,
+    #[serde(default)]
+    pub citations: Option<Vec<ChatGPTCitation>>,
+    #[serde(default, rename = "_cite_metadata")]
+    pub cite_metadata: Option<ChatGPTCiteMetadata>,
+    #[serde(default)]
+    pub search_result_groups: Option<Vec<ChatGPTSearchResultGroup>>,
+    #[serde(default)]
+    pub canvas: Option<ChatGPTCanvasMetadata>,
+}
+
+/// The canvas document a `canmore` tool response refers to.
+#[derive(Deserialize)]
+pub struct ChatGPTCanvasMetadata {
+    #[serde(default)]
+    pub textdoc_id: Option<String>,
+}
+
+/// Payload of a `canmore.create_textdoc` or `canmore.update_textdoc`
+/// call, sent as JSON code. `type` is `document` or `code/<language>`.
+#[derive(Deserialize)]
+pub struct ChatGPTCanvasCall {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default, rename = "type")]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(default)]
+    pub updates: Vec<ChatGPTCanvasUpdate>,
+}
+
+/// A regex replacement in a canvas document. Only the first match is
+/// replaced unless `multiple` is set.
+#[derive(Deserialize)]
+pub struct ChatGPTCanvasUpdate {
+    pub pattern: String,
+    #[serde(default)]
+    pub multiple: bool,
+    #[serde(default)]
+    pub replacement: String,
+}
+
+/// Pages an older browsing display can be cited from, which give the URLs
+/// its result text leaves out.
+#[derive(Deserialize)]
+pub struct ChatGPTCiteMetadata {
+    #[serde(default)]
+    pub metadata_list: Option<Vec<ChatGPTCitationMetadata>>,
+}
+
+/// Search hits of a web search tool message, grouped by domain.
+#[derive(Deserialize)]
+pub struct ChatGPTSearchResultGroup {
+    #[serde(default)]
+    pub domain: Option<String>,
+    #[serde(default)]
+    pub entries: Option<Vec<ChatGPTSearchResult>>,
+}
+
+#[derive(Deserialize)]
+pub struct ChatGPTSearchResult {
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub attribution: Option<String>,
+}
+
+/// What a marker in the message text stands for. `matched_text` is the
+/// marker itself; cited pages are either listed in `items` or, for a
+/// single page, given directly. Entities carry the `name` to show.
+#[derive(Deserialize)]
+#[allow(dead_code)]
+pub struct ChatGPTContentReference {
+    #[serde(default)]
+    pub matched_text: Option<String>,
+    #[serde(default, rename = "type")]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub attribution: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub items: Option<Vec<ChatGPTReferenceItem>>,
+}
+
+#[derive(Deserialize)]
+pub struct ChatGPTReferenceItem {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub attribution: Option<String>,
+}
+
+/// A citation in the older format, which marks the cited span of the
+/// text by character offsets instead of by its text.
+#[derive(Deserialize)]
+pub struct ChatGPTCitation {
+    pub start_ix: usize,
+    pub end_ix: usize,
+    #[serde(default)]
+    pub metadata: Option<ChatGPTCitationMetadata>,
+}
+
+#[derive(Deserialize)]
+pub struct ChatGPTCitationMetadata {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+/// Result of a code interpreter run, attached to its `execution_output`
+/// message. `messages` holds the printed streams and generated images.
+#[derive(Deserialize)]
+#[allow(dead_code)]
+pub struct ChatGPTAggregateResult {
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub messages: Vec<ChatGPTExecutionMessage>,
+    #[serde(default)]
+    pub in_kernel_exception: Option<ChatGPTKernelException>,
+}
+
+#[derive(Deserialize)]
+pub struct ChatGPTExecutionMessage {
+    pub message_type: String,
+    #[serde(default)]
+    pub stream_name: Option<String>,
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub image_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+pub struct ChatGPTKernelException {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub traceback: Vec<String>,
 }
 
 #[derive(Deserialize)]
@@ -80,27 +266,50 @@ pub struct ChatGPTMetadata {
 #[allow(dead_code)]
 pub enum ChatGPTContentPart {
     Text(String),
-    ImageAssetPointer(ImageAssetPointer),
+    ImageAssetPointer(Box<ImageAssetPointer>),
+    Other(serde_json::Value),
 }
 
+/// An uploaded or generated image. `asset_pointer` names the file, e.g.
+/// `file-service://file-AbC123` or `sediment://file_00000000abcd`.
 #[derive(Deserialize)]
 #[allow(dead_code)]
 pub struct ImageAssetPointer {
     pub content_type: String,
     pub asset_pointer: String,
-    pub size_bytes: u64,
-    pub width: u32,
-    pub height: u32,
-    pub fovea: Option<String>,
-    pub metadata: ImageMetadata,
+    #[serde(default)]
+    pub size_bytes: Option<u64>,
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
+    #[serde(default)]
+    pub fovea: Option<serde_json::Value>,
+    #[serde(default)]
+    pub metadata: Option<ImageMetadata>,
 }
 
 #[derive(Deserialize)]
 #[allow(dead_code)]
 pub struct ImageMetadata {
-    pub dalle: Option<String>,
-    pub gizmo: Option<String>,
-    pub emu_omit_glimpse_image: Option<String>,
-    pub emu_patches_override: Option<String>,
-    pub sanitized: bool,
+    #[serde(default)]
+    pub dalle: Option<DalleMetadata>,
+    #[serde(default)]
+    pub gizmo: Option<serde_json::Value>,
+    #[serde(default)]
+    pub emu_omit_glimpse_image: Option<serde_json::Value>,
+    #[serde(default)]
+    pub emu_patches_override: Option<serde_json::Value>,
+    #[serde(default)]
+    pub sanitized: Option<bool>,
+}
+
+/// How a DALL·E image was generated.
+#[derive(Deserialize)]
+#[allow(dead_code)]
+pub struct DalleMetadata {
+    #[serde(default)]
+    pub gen_id: Option<String>,
+    #[serde(default)]
+    pub prompt: Option<String>,
 }
@@ -2,23 +2,56 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize)]
 pub struct ClaudeChat {
-    pub name: String,
+    #[serde(default)]
+    pub uuid: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
     pub created_at: String,
     pub updated_at: String,
     pub chat_messages: Vec<ClaudeMessage>,
+    #[serde(default)]
+    pub current_leaf_message_uuid: Option<String>,
+    #[serde(default)]
+    pub project_uuid: Option<String>,
+    #[serde(default)]
+    pub project: Option<ClaudeProjectRef>,
+}
+
+#[derive(Deserialize)]
+pub struct ClaudeProjectRef {
+    #[serde(default)]
+    pub uuid: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// A project from the `projects.json` file of a Claude account export
+#[derive(Deserialize)]
+#[allow(dead_code)]
+pub struct ClaudeProject {
+    pub uuid: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
 }
 
 #[derive(Deserialize)]
 #[allow(dead_code)]
 pub struct ClaudeMessage {
     pub uuid: String,
+    #[serde(default)]
     pub text: String,
+    #[serde(default)]
     pub index: u32,
     pub sender: String,
     pub created_at: String,
+    #[serde(default)]
     pub content: Vec<ClaudeContent>,
+    #[serde(default)]
     pub attachments: Vec<ClaudeAttachment>,
-    pub updated_at: String,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+    #[serde(default)]
     pub truncated: bool,
     #[serde(default)]
     pub stop_reason: Option<String>,
@@ -28,7 +61,8 @@ pub struct ClaudeMessage {
     pub files_v2: Vec<FileV2>,
     #[serde(default)]
     pub sync_sources: Vec<String>,
-    pub parent_message_uuid: String,
+    #[serde(default)]
+    pub parent_message_uuid: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -42,38 +76,66 @@ pub struct ClaudeContent {
     pub stop_timestamp: Option<String>,
     pub text: Option<String>,
     pub name: Option<String>,
-    #[serde(rename = "input")]
-    pub artifact: Option<ClaudeArtifact>,
+    pub input: Option<serde_json::Value>,
     pub thinking: Option<String>,
     pub summaries: Option<Vec<ClaudeSummary>>,
 }
 
+impl ClaudeContent {
+    pub fn artifact(&self) -> Option<ClaudeArtifact> {
+        self.input
+            .clone()
+            .and_then(|input| serde_json::from_value(input).ok())
+    }
+}
+
 #[derive(Deserialize)]
 pub struct ClaudeSummary {
     pub summary: String,
 }
 
+/// Input of an `artifacts` tool call. `command` is `create`, `update` or
+/// `rewrite`; updates replace `old_str` with `new_str` in the previous
+/// version instead of carrying the content.
 #[derive(Deserialize)]
 pub struct ClaudeArtifact {
     #[serde(default)]
     pub id: Option<String>,
+    #[serde(default, rename = "type")]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub command: Option<String>,
     #[serde(default)]
     pub content: Option<String>,
     #[serde(default)]
     pub code: Option<String>,
+    #[serde(default)]
+    pub old_str: Option<String>,
+    #[serde(default)]
+    pub new_str: Option<String>,
 }
 
 #[derive(Deserialize)]
 pub struct ClaudeAttachment {
     pub file_name: String,
-    pub extracted_content: String,
+    #[serde(default)]
+    pub file_size: Option<u64>,
+    #[serde(default)]
+    pub extracted_content: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct File {
+    #[serde(default)]
     pub file_kind: String,
+    #[serde(default)]
     pub file_uuid: String,
     pub file_name: String,
+    #[serde(default)]
     pub created_at: String,
     #[serde(default)]
     pub thumbnail_url: Option<String>,
@@ -87,9 +149,12 @@ pub struct File {
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct FileV2 {
+    #[serde(default)]
     pub file_kind: String,
+    #[serde(default)]
     pub file_uuid: String,
     pub file_name: String,
+    #[serde(default)]
     pub created_at: String,
     #[serde(default)]
     pub thumbnail_url: Option<String>,
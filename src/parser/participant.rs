@@ -1,57 +1,30 @@
-use crate::config::MarkdownConfig;
+use crate::config::{ChatSource, MarkdownConfig};
+use crate::parser::conversation::Role;
 
-pub(crate) struct ClaudeParticipantMapper<'a> {
+pub(crate) struct ParticipantMapper<'a> {
     config: &'a MarkdownConfig,
+    ai_name: &'a str,
 }
 
-impl<'a> ClaudeParticipantMapper<'a> {
-    pub fn new(config: &'a MarkdownConfig) -> Self {
-        Self { config }
+impl<'a> ParticipantMapper<'a> {
+    pub fn new(config: &'a MarkdownConfig, source: ChatSource) -> Self {
+        let ai_name = config
+            .ai_name
+            .as_deref()
+            .unwrap_or_else(|| source.default_ai_name());
+        Self { config, ai_name }
     }
 
-    pub fn get_name<'b>(&'b self, sender: &'b str) -> &'b str {
-        match sender {
-            "human" => &self.config.user_name,
-            "assistant" => &self.config.ai_name,
-            other => other,
-        }
-    }
-}
-
-pub(crate) struct ChatGPTParticipantMapper<'a> {
-    config: &'a MarkdownConfig,
-}
-
-impl<'a> ChatGPTParticipantMapper<'a> {
-    pub fn new(config: &'a MarkdownConfig) -> Self {
-        Self { config }
-    }
-
-    pub fn get_name<'b>(&'b self, sender: &'b str) -> &'b str {
-        match sender {
-            "user" => &self.config.user_name,
-            "assistant" => &self.config.ai_name,
-            "system" => &self.config.ai_name,
-            "tool" => &self.config.ai_name,
-            other => other,
-        }
-    }
-}
-
-pub(crate) struct DeepSeekParticipantMapper<'a> {
-    config: &'a MarkdownConfig,
-}
-
-impl<'a> DeepSeekParticipantMapper<'a> {
-    pub fn new(config: &'a MarkdownConfig) -> Self {
-        Self { config }
-    }
-
-    pub fn get_name<'b>(&'b self, sender: &'b str) -> &'b str {
-        match sender {
-            "USER" => &self.config.user_name,
-            "ASSISTANT" => &self.config.ai_name,
-            other => other,
+    pub fn get_name<'b>(&self, role: &'b Role) -> &'b str
+    where
+        'a: 'b,
+    {
+        match role {
+            Role::User => &self.config.user_name,
+            Role::Assistant => self.ai_name,
+            Role::System => "System",
+            Role::Tool => "Tool",
+            Role::Other(other) => other,
         }
     }
 }
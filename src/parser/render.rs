@@ -0,0 +1,334 @@
+use crate::config::{MarkdownConfig, OutputFormat};
+use crate::parser::conversation::{Block, Conversation, Role, Turn};
+use crate::parser::error::Result;
+use crate::parser::participant::ParticipantMapper;
+use crate::parser::timestamp::TimeFormatter;
+use crate::parser::{dataset, html, json, markdown};
+use std::collections::HashMap;
+
+/// The conversation as it is shown, shared by every output format: names
+/// and times are resolved and hidden content is already filtered out.
+pub(crate) struct ConversationView<'a> {
+    pub id: Option<&'a str>,
+    pub title: String,
+    pub platform: &'static str,
+    pub project: Option<&'a str>,
+    pub first_message: String,
+    pub last_message: String,
+    /// RFC 3339 versions of the first and last message times
+    pub created_at: String,
+    pub updated_at: String,
+    pub source_file: Option<&'a str>,
+    /// Models that answered on the exported path, in order of first use
+    pub models: Vec<&'a str>,
+    /// Whether the models are shown in the header and next to each answer
+    pub model_headers: bool,
+    /// Whether to write the hidden anchors update mode relies on
+    pub anchors: bool,
+    /// Directory artifact files are linked from, relative to the output
+    pub artifact_dir: Option<&'a str>,
+    /// Whether browsed pages are folded away
+    pub collapse_browsing: bool,
+    /// Messages the platform hides, shown apart from the conversation
+    pub hidden_context: Vec<TurnView<'a>>,
+    pub turns: Vec<TurnView<'a>>,
+    pub branches: Vec<BranchView<'a>>,
+}
+
+pub(crate) struct TurnView<'a> {
+    pub turn: &'a Turn,
+    pub sections: Vec<Section<'a>>,
+    /// The previous model and this turn's, when it answered with a
+    /// different model than the turn before it and models are shown
+    pub model_switch: Option<(&'a str, &'a str)>,
+}
+
+/// Consecutive blocks of a turn shown under one header: either the
+/// thinking that leads up to an answer, or the answer itself.
+pub(crate) struct Section<'a> {
+    pub thinking: bool,
+    pub sender: &'a str,
+    /// Model shown next to the sender, if enabled
+    pub model: Option<&'a str>,
+    pub time: String,
+    pub blocks: Vec<&'a Block>,
+}
+
+pub(crate) struct BranchView<'a> {
+    /// Position of the branch among the conversation's, from 1, which
+    /// also names its artifact files
+    pub number: usize,
+    /// "Sender @ time" of the turn the branch continues from
+    pub forks_after: Option<String>,
+    pub turns: Vec<TurnView<'a>>,
+}
+
+/// Describes an image the export did not include, e.g.
+/// `Image file-AbC123 (1024×1024, 52480 bytes)`.
+pub(crate) fn image_placeholder(
+    asset: &str,
+    width: Option<u32>,
+    height: Option<u32>,
+    size_bytes: Option<u64>,
+) -> String {
+    let mut details = Vec::new();
+    if let (Some(width), Some(height)) = (width, height) {
+        details.push(format!("{}×{}", width, height));
+    }
+    if let Some(size) = size_bytes {
+        details.push(format!("{} bytes", size));
+    }
+
+    if details.is_empty() {
+        format!("Image {}", asset)
+    } else {
+        format!("Image {} ({})", asset, details.join(", "))
+    }
+}
+
+/// The model that produced a turn. Prompts are not attributed to one,
+/// although some platforms record the model selected when they were sent.
+fn turn_model(turn: &Turn) -> Option<&str> {
+    match turn.role {
+        Role::User => None,
+        _ => turn.model.as_deref().filter(|model| !model.is_empty()),
+    }
+}
+
+fn is_visible(block: &Block, config: &MarkdownConfig) -> bool {
+    match block {
+        Block::Text(text) => !text.is_empty(),
+        Block::Thinking { text, .. } => config.reasoning && !text.is_empty(),
+        Block::Attachment { .. } => config.attachments,
+        Block::Execution { .. } => config.code_execution,
+        Block::ToolCall { .. } => false,
+        _ => true,
+    }
+}
+
+struct ViewBuilder<'a> {
+    config: &'a MarkdownConfig,
+    participant_mapper: ParticipantMapper<'a>,
+    time_formatter: TimeFormatter,
+}
+
+impl<'a> ViewBuilder<'a> {
+    /// Groups the visible blocks of a turn into sections, or returns
+    /// `None` if there is nothing to show, e.g. a thinking-only turn
+    /// when reasoning is disabled. `previous_model` is the model of the
+    /// turns shown before it, which is updated to this turn's.
+    fn turn(&self, turn: &'a Turn, previous_model: &mut Option<&'a str>) -> Option<TurnView<'a>> {
+        let sender = self.participant_mapper.get_name(&turn.role);
+        let model = turn_model(turn);
+        let mut sections: Vec<Section<'a>> = Vec::new();
+
+        for block in turn.blocks.iter().filter(|b| is_visible(b, self.config)) {
+            let thinking = matches!(block, Block::Thinking { .. });
+            match sections.last_mut() {
+                Some(section) if section.thinking == thinking => section.blocks.push(block),
+                _ => {
+                    // Thinking is timed from when it started, if known
+                    let timestamp = match block {
+                        Block::Thinking { started_at, .. } => {
+                            started_at.as_ref().or(turn.created_at.as_ref())
+                        }
+                        _ => turn.created_at.as_ref(),
+                    };
+                    sections.push(Section {
+                        thinking,
+                        sender,
+                        model: model.filter(|_| self.config.model_headers),
+                        time: self.time_formatter.format_opt(timestamp),
+                        blocks: vec![block],
+                    });
+                }
+            }
+        }
+
+        if sections.is_empty() {
+            return None;
+        }
+
+        let model_switch = match (*previous_model, model) {
+            (Some(previous), Some(model)) if self.config.model_headers && previous != model => {
+                Some((previous, model))
+            }
+            _ => None,
+        };
+        if model.is_some() {
+            *previous_model = model;
+        }
+
+        Some(TurnView {
+            turn,
+            sections,
+            model_switch,
+        })
+    }
+
+    fn label(&self, turn: &Turn) -> String {
+        format!(
+            "{} @ {}",
+            self.participant_mapper.get_name(&turn.role),
+            self.time_formatter.format_opt(turn.created_at.as_ref())
+        )
+    }
+}
+
+/// Walks the conversation, calling `on_turn` once for every turn of the
+/// exported path so callers can report progress.
+pub(crate) fn build_view<'a>(
+    conversation: &'a Conversation,
+    config: &'a MarkdownConfig,
+    mut on_turn: impl FnMut(),
+) -> ConversationView<'a> {
+    let builder = ViewBuilder {
+        config,
+        participant_mapper: ParticipantMapper::new(config, conversation.source),
+        time_formatter: TimeFormatter::new(&config.timezone),
+    };
+
+    let hidden_context = conversation
+        .hidden_context
+        .iter()
+        .filter_map(|turn| builder.turn(turn, &mut None))
+        .collect();
+
+    // Labels of shown turns, so branches can say where they fork, and
+    // the model in use as of each, so they can say when they switch
+    let mut labels: HashMap<&str, String> = HashMap::new();
+    let mut models_at: HashMap<&str, &str> = HashMap::new();
+
+    let mut turns = Vec::with_capacity(conversation.turns.len());
+    let mut model = None;
+    for turn in &conversation.turns {
+        on_turn();
+        if let Some(view) = builder.turn(turn, &mut model) {
+            if let Some(id) = &turn.id {
+                labels.insert(id, builder.label(turn));
+                if let Some(model) = model {
+                    models_at.insert(id, model);
+                }
+            }
+            turns.push(view);
+        }
+    }
+
+    let mut models: Vec<&str> = Vec::new();
+    for model in turns.iter().filter_map(|view| turn_model(view.turn)) {
+        if !models.contains(&model) {
+            models.push(model);
+        }
+    }
+
+    let mut branches = Vec::new();
+    if config.branches {
+        for (i, branch) in conversation.branches.iter().enumerate() {
+            let forks_after = branch
+                .forks_after
+                .as_deref()
+                .and_then(|id| labels.get(id))
+                .cloned();
+
+            let mut model = branch
+                .forks_after
+                .as_deref()
+                .and_then(|id| models_at.get(id))
+                .copied();
+            let mut branch_turns = Vec::with_capacity(branch.turns.len());
+            for turn in &branch.turns {
+                if let Some(view) = builder.turn(turn, &mut model) {
+                    if let Some(id) = &turn.id {
+                        labels.insert(id, builder.label(turn));
+                        if let Some(model) = model {
+                            models_at.insert(id, model);
+                        }
+                    }
+                    branch_turns.push(view);
+                }
+            }
+
+            if !branch_turns.is_empty() {
+                branches.push(BranchView {
+                    number: i + 1,
+                    forks_after,
+                    turns: branch_turns,
+                });
+            }
+        }
+    }
+
+    ConversationView {
+        id: conversation.id.as_deref(),
+        title: config
+            .title
+            .clone()
+            .unwrap_or_else(|| conversation.title.clone()),
+        platform: conversation.source.platform_name(),
+        project: conversation.project_name.as_deref(),
+        first_message: builder.time_formatter.format(&conversation.created_at),
+        last_message: builder.time_formatter.format(&conversation.updated_at),
+        created_at: builder
+            .time_formatter
+            .format_rfc3339(&conversation.created_at),
+        updated_at: builder
+            .time_formatter
+            .format_rfc3339(&conversation.updated_at),
+        source_file: config.source_file.as_deref(),
+        models,
+        model_headers: config.model_headers,
+        anchors: config.update,
+        artifact_dir: config.artifact_dir.as_deref(),
+        collapse_browsing: config.collapse_browsing,
+        hidden_context,
+        turns,
+        branches,
+    }
+}
+
+/// Renders the conversation for an output file that may already hold an
+/// earlier export of it. In update mode, Markdown formats only append the
+/// turns missing from `existing`, and `None` means there were none. Other
+/// formats are rendered in full.
+pub fn render_update(
+    conversation: &Conversation,
+    config: &MarkdownConfig,
+    existing: Option<&str>,
+    on_turn: impl FnMut(),
+) -> Result<Option<String>> {
+    match (config.format, existing) {
+        (OutputFormat::Markdown, Some(existing)) if config.update => {
+            markdown::update(existing, &build_view(conversation, config, on_turn))
+        }
+        (OutputFormat::Obsidian, Some(existing)) if config.update => {
+            markdown::update_obsidian(existing, &build_view(conversation, config, on_turn))
+        }
+        _ => render_with_progress(conversation, config, on_turn).map(Some),
+    }
+}
+
+/// Renders the conversation in the configured output format.
+pub fn render(conversation: &Conversation, config: &MarkdownConfig) -> Result<String> {
+    render_with_progress(conversation, config, || {})
+}
+
+/// Renders the conversation in the configured output format, calling
+/// `on_turn` once for every turn processed so callers can report progress.
+pub fn render_with_progress(
+    conversation: &Conversation,
+    config: &MarkdownConfig,
+    on_turn: impl FnMut(),
+) -> Result<String> {
+    match config.format {
+        OutputFormat::Markdown => markdown::write(&build_view(conversation, config, on_turn)),
+        OutputFormat::Obsidian => {
+            markdown::write_obsidian(&build_view(conversation, config, on_turn))
+        }
+        OutputFormat::Html => html::write(&build_view(conversation, config, on_turn)),
+        // JSON keeps the structure of the conversation instead of a view of it
+        OutputFormat::Json => json::write(conversation, config, on_turn),
+        OutputFormat::OpenAi | OutputFormat::ShareGpt => {
+            dataset::render_dataset(&[conversation], config)
+        }
+    }
+}
@@ -1,57 +1,43 @@
-use super::error::Result;
+use super::error::{ParseError, Result};
 use chrono::{DateTime, TimeZone, Utc};
 use chrono_tz::Tz;
 
 pub struct TimeFormatter {
     timezone: Tz,
-    format_type: TimeFormat,
-}
-
-pub enum TimeFormat {
-    Rfc3339,
-    Unix,
 }
 
 impl TimeFormatter {
-    pub fn new(timezone: &str, format_type: TimeFormat) -> Self {
-        let timezone = match timezone.parse() {
-            Ok(tz) => tz,
-            Err(_) => {
-                println!(
-                    "Warning: Invalid timezone '{}', falling back to UTC",
-                    timezone
-                );
-                Tz::UTC
-            }
-        };
+    /// An invalid timezone falls back to UTC.
+    pub fn new(timezone: &str) -> Self {
         Self {
-            timezone,
-            format_type,
+            timezone: timezone.parse().unwrap_or(Tz::UTC),
         }
     }
 
-    pub fn format_iso(&self, value: &str) -> Result<String> {
-        let utc_dt = match self.format_type {
-            TimeFormat::Rfc3339 => DateTime::parse_from_rfc3339(value)?.with_timezone(&Utc),
-            TimeFormat::Unix => {
-                let ts = value.parse::<f64>()?;
-                Utc.timestamp_opt(ts as i64, 0)
-                    .single()
-                    .ok_or(super::error::ParseError::InvalidTimestamp(ts))?
-            }
-        };
-
-        let local_time = utc_dt.with_timezone(&self.timezone);
-        Ok(format!("{}", local_time.format("%Y-%m-%d %I:%M %p %Z")))
+    pub fn format(&self, value: &DateTime<Utc>) -> String {
+        let local_time = value.with_timezone(&self.timezone);
+        format!("{}", local_time.format("%Y-%m-%d %I:%M %p %Z"))
     }
 
-    pub fn format_unix(&self, value: f64) -> Result<String> {
-        let utc_dt = Utc
-            .timestamp_opt(value as i64, 0)
-            .single()
-            .ok_or(super::error::ParseError::InvalidTimestamp(value))?;
+    /// RFC 3339 in the configured timezone, for machine-readable metadata.
+    pub fn format_rfc3339(&self, value: &DateTime<Utc>) -> String {
+        value.with_timezone(&self.timezone).to_rfc3339()
+    }
 
-        let local_time = utc_dt.with_timezone(&self.timezone);
-        Ok(format!("{}", local_time.format("%Y-%m-%d %I:%M %p %Z")))
+    pub fn format_opt(&self, value: Option<&DateTime<Utc>>) -> String {
+        match value {
+            Some(value) => self.format(value),
+            None => "Unknown Time".to_string(),
+        }
     }
 }
+
+pub fn parse_rfc3339(value: &str) -> Result<DateTime<Utc>> {
+    Ok(DateTime::parse_from_rfc3339(value)?.with_timezone(&Utc))
+}
+
+pub fn parse_unix(value: f64) -> Result<DateTime<Utc>> {
+    Utc.timestamp_opt(value as i64, 0)
+        .single()
+        .ok_or(ParseError::InvalidTimestamp(value))
+}
@@ -0,0 +1,212 @@
+use std::collections::{HashMap, HashSet};
+
+/// Parent links between the messages of a chat. Regenerated answers and
+/// edited prompts make the chat a tree, of which only one path is the
+/// conversation the user sees.
+#[derive(Default)]
+pub struct MessageTree {
+    order: Vec<String>,
+    parents: HashMap<String, Option<String>>,
+    children: HashMap<String, Vec<String>>,
+}
+
+/// A run of messages that diverges from the paths already covered,
+/// starting right after the message `forks_after`.
+pub struct BranchPath {
+    pub forks_after: Option<String>,
+    pub ids: Vec<String>,
+}
+
+impl MessageTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a message. Siblings keep the order they were inserted in,
+    /// so the last inserted child is treated as the latest one.
+    pub fn insert(&mut self, id: String, parent: Option<String>) {
+        if self.parents.contains_key(&id) {
+            return;
+        }
+        if let Some(parent) = &parent {
+            self.children
+                .entry(parent.clone())
+                .or_default()
+                .push(id.clone());
+        }
+        self.order.push(id.clone());
+        self.parents.insert(id, parent);
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.parents.contains_key(id)
+    }
+
+    fn children(&self, id: &str) -> &[String] {
+        self.children.get(id).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Messages without a known parent. Some platforms point the first
+    /// message at a placeholder id that is not part of the chat.
+    fn roots(&self) -> impl Iterator<Item = &String> {
+        self.order.iter().filter(|id| match &self.parents[*id] {
+            Some(parent) => !self.parents.contains_key(parent),
+            None => true,
+        })
+    }
+
+    /// The first message of the chat.
+    pub fn root(&self) -> Option<&String> {
+        self.roots().next()
+    }
+
+    /// The leaf reached by always following the latest child,
+    /// starting from the latest root.
+    pub fn latest_leaf(&self) -> Option<String> {
+        let mut current = self.roots().last()?;
+        while let Some(child) = self.children(current).last() {
+            current = child;
+        }
+        Some(current.clone())
+    }
+
+    /// The ids from the root down to `leaf`, inclusive.
+    pub fn path_to(&self, leaf: &str) -> Vec<String> {
+        let mut path = Vec::new();
+        let mut seen = HashSet::new();
+        let mut current = Some(leaf.to_string());
+
+        while let Some(id) = current {
+            if !self.contains(&id) || !seen.insert(id.clone()) {
+                break;
+            }
+            current = self.parents[&id].clone();
+            path.push(id);
+        }
+
+        path.reverse();
+        path
+    }
+
+    fn leaves(&self) -> Vec<&String> {
+        let mut leaves = Vec::new();
+        let mut stack: Vec<&String> = self.roots().collect();
+        stack.reverse();
+
+        while let Some(id) = stack.pop() {
+            let children = self.children(id);
+            if children.is_empty() {
+                leaves.push(id);
+            }
+            stack.extend(children.iter().rev());
+        }
+
+        leaves
+    }
+
+    /// Splits every path not covered by `main` into the part that diverges
+    /// from what has already been covered, so each message appears once.
+    pub fn branches(&self, main: &[String]) -> Vec<BranchPath> {
+        let mut covered: HashSet<&str> = main.iter().map(String::as_str).collect();
+        let mut branches = Vec::new();
+
+        for leaf in self.leaves() {
+            if covered.contains(leaf.as_str()) {
+                continue;
+            }
+
+            let path = self.path_to(leaf);
+            let fork = path.iter().rposition(|id| covered.contains(id.as_str()));
+            let (forks_after, ids) = match fork {
+                Some(index) => (Some(path[index].clone()), path[index + 1..].to_vec()),
+                None => (None, path.clone()),
+            };
+
+            for id in &path {
+                if let Some(id) = self.parents.get_key_value(id).map(|(id, _)| id) {
+                    covered.insert(id.as_str());
+                }
+            }
+            branches.push(BranchPath { forks_after, ids });
+        }
+
+        branches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `r -> u1 -> {a1, a2}`, with `a2 -> u2` regenerated after `a1`.
+    fn tree() -> MessageTree {
+        let mut tree = MessageTree::new();
+        for (id, parent) in [
+            ("r", None),
+            ("u1", Some("r")),
+            ("a1", Some("u1")),
+            ("a2", Some("u1")),
+            ("u2", Some("a2")),
+        ] {
+            tree.insert(id.to_string(), parent.map(str::to_string));
+        }
+        tree
+    }
+
+    #[test]
+    fn latest_leaf_follows_the_last_child() {
+        let tree = tree();
+        assert_eq!(tree.latest_leaf().as_deref(), Some("u2"));
+        assert_eq!(tree.path_to("u2"), ["r", "u1", "a2", "u2"]);
+    }
+
+    #[test]
+    fn path_to_stops_at_unknown_parents() {
+        let mut tree = MessageTree::new();
+        tree.insert("a".to_string(), Some("placeholder".to_string()));
+        tree.insert("b".to_string(), Some("a".to_string()));
+
+        assert_eq!(tree.root().map(String::as_str), Some("a"));
+        assert_eq!(tree.path_to("b"), ["a", "b"]);
+        assert!(tree.path_to("missing").is_empty());
+    }
+
+    #[test]
+    fn branches_fork_after_the_covered_message() {
+        let tree = tree();
+        let main = tree.path_to("u2");
+        let branches = tree.branches(&main);
+
+        assert_eq!(branches.len(), 1);
+        assert_eq!(branches[0].forks_after.as_deref(), Some("u1"));
+        assert_eq!(branches[0].ids, ["a1"]);
+    }
+
+    #[test]
+    fn branches_cover_each_message_once() {
+        let mut tree = tree();
+        tree.insert("u3".to_string(), Some("a1".to_string()));
+        tree.insert("u4".to_string(), Some("a1".to_string()));
+        let main = tree.path_to("u2");
+        let branches = tree.branches(&main);
+
+        let ids: Vec<_> = branches
+            .iter()
+            .map(|branch| (branch.forks_after.as_deref(), branch.ids.clone()))
+            .collect();
+        assert_eq!(
+            ids,
+            [
+                (Some("u1"), vec!["a1".to_string(), "u3".to_string()]),
+                (Some("a1"), vec!["u4".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn inserting_an_id_twice_keeps_the_first() {
+        let mut tree = tree();
+        tree.insert("a1".to_string(), Some("u2".to_string()));
+        assert_eq!(tree.path_to("a1"), ["r", "u1", "a1"]);
+    }
+}
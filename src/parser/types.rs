@@ -24,9 +24,12 @@ pub enum ChatGPTContentType {
     TetherQuote,
     MultimodalText,
     UserEditableContext,
+    ModelEditableContext,
     Tool,
     System,
     Code,
+    ExecutionOutput,
+    TetherBrowsingDisplay,
     Unknown(String),
 }
 
@@ -37,29 +40,13 @@ impl From<&str> for ChatGPTContentType {
             "tether_quote" => ChatGPTContentType::TetherQuote,
             "multimodal_text" => ChatGPTContentType::MultimodalText,
             "user_editable_context" => ChatGPTContentType::UserEditableContext,
+            "model_editable_context" => ChatGPTContentType::ModelEditableContext,
             "tool" => ChatGPTContentType::Tool,
             "system" => ChatGPTContentType::System,
             "code" => ChatGPTContentType::Code,
+            "execution_output" => ChatGPTContentType::ExecutionOutput,
+            "tether_browsing_display" => ChatGPTContentType::TetherBrowsingDisplay,
             other => ChatGPTContentType::Unknown(other.to_string()),
         }
     }
 }
-
-#[derive(Debug)]
-pub enum DeepSeekContentType {
-    Text,
-    Thinking,
-    Unknown(String),
-}
-
-impl From<&str> for DeepSeekContentType {
-    fn from(s: &str) -> Self {
-        // DeepSeek doesn't explicitly specify content types in the JSON
-        // We'll infer based on the message properties
-        match s {
-            "text" => DeepSeekContentType::Text,
-            "thinking" => DeepSeekContentType::Thinking,
-            other => DeepSeekContentType::Unknown(other.to_string()),
-        }
-    }
-}